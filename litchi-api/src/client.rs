@@ -0,0 +1,168 @@
+use async_trait::async_trait;
+use litchitool::mission::LitchiMission;
+
+use crate::{error::LitchiApiError, transport::HttpTransport, LitchiApi, Mission, ObjectId};
+
+/// Object safe interface of a logged in [`LitchiApi`], so applications can store it as
+/// `Box<dyn LitchiApiClient>` and substitute their own implementation in tests
+#[async_trait]
+pub trait LitchiApiClient: Send + Sync {
+    /// See [`LitchiApi::upload`]
+    async fn upload(&self, mission: &LitchiMission, name: &str)
+        -> Result<ObjectId, LitchiApiError>;
+
+    /// See [`LitchiApi::missions`]
+    async fn missions(&self) -> Result<Vec<Mission>, LitchiApiError>;
+
+    /// See [`LitchiApi::download_mission`]
+    async fn download_mission(&self, mission: &Mission) -> Result<LitchiMission, LitchiApiError>;
+
+    /// See [`LitchiApi::delete_mission`]
+    async fn delete_mission(&self, mission_id: ObjectId) -> Result<(), LitchiApiError>;
+
+    /// See [`LitchiApi::sync_devices`]
+    async fn sync_devices(&self) -> Result<(), LitchiApiError>;
+}
+
+#[async_trait]
+impl<T: HttpTransport + Send + Sync> LitchiApiClient for LitchiApi<T> {
+    async fn upload(
+        &self,
+        mission: &LitchiMission,
+        name: &str,
+    ) -> Result<ObjectId, LitchiApiError> {
+        LitchiApi::upload(self, mission, name).await
+    }
+
+    async fn missions(&self) -> Result<Vec<Mission>, LitchiApiError> {
+        LitchiApi::missions(self).await
+    }
+
+    async fn download_mission(&self, mission: &Mission) -> Result<LitchiMission, LitchiApiError> {
+        LitchiApi::download_mission(self, mission).await
+    }
+
+    async fn delete_mission(&self, mission_id: ObjectId) -> Result<(), LitchiApiError> {
+        LitchiApi::delete_mission(self, mission_id).await
+    }
+
+    async fn sync_devices(&self) -> Result<(), LitchiApiError> {
+        LitchiApi::sync_devices(self).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use litchitool::mission::{Coordinate, LitchiMission};
+
+    use super::*;
+    use crate::MissionFile;
+
+    /// Keeps uploaded missions in memory
+    #[derive(Default)]
+    struct StubClient {
+        missions: Mutex<Vec<(Mission, LitchiMission)>>,
+    }
+
+    #[async_trait]
+    impl LitchiApiClient for StubClient {
+        async fn upload(
+            &self,
+            mission: &LitchiMission,
+            name: &str,
+        ) -> Result<ObjectId, LitchiApiError> {
+            let mut missions = self.missions.lock().unwrap();
+            let object_id = ObjectId(format!("mission{}", missions.len()));
+            let uploaded = Mission {
+                object_id: object_id.clone(),
+                location: mission
+                    .waypoints()
+                    .first()
+                    .map_or(Coordinate(0., 0.), |waypoint| waypoint.coordinate.clone()),
+                name: name.to_string(),
+                user_id: ObjectId("user1".to_string()),
+                file: MissionFile {
+                    name: format!("{name}.mission"),
+                    url: String::new(),
+                },
+                metadata: Default::default(),
+            };
+            missions.push((uploaded, mission.clone()));
+            Ok(object_id)
+        }
+
+        async fn missions(&self) -> Result<Vec<Mission>, LitchiApiError> {
+            let missions = self.missions.lock().unwrap();
+            Ok(missions
+                .iter()
+                .map(|(mission, _)| mission.clone())
+                .collect())
+        }
+
+        async fn download_mission(
+            &self,
+            mission: &Mission,
+        ) -> Result<LitchiMission, LitchiApiError> {
+            let missions = self.missions.lock().unwrap();
+            missions
+                .iter()
+                .find(|(uploaded, _)| uploaded.object_id == mission.object_id)
+                .map(|(_, mission)| mission.clone())
+                .ok_or(LitchiApiError::MissionFormatError(
+                    "no such mission".to_string(),
+                ))
+        }
+
+        async fn delete_mission(&self, mission_id: ObjectId) -> Result<(), LitchiApiError> {
+            self.missions
+                .lock()
+                .unwrap()
+                .retain(|(mission, _)| mission.object_id != mission_id);
+            Ok(())
+        }
+
+        async fn sync_devices(&self) -> Result<(), LitchiApiError> {
+            Ok(())
+        }
+    }
+
+    /// Application code that only knows the trait object
+    async fn replace_mission(
+        client: &dyn LitchiApiClient,
+        mission: &LitchiMission,
+        name: &str,
+    ) -> Result<ObjectId, LitchiApiError> {
+        for existing in client.missions().await? {
+            if existing.name == name {
+                client.delete_mission(existing.object_id).await?;
+            }
+        }
+
+        let uploaded = client.upload(mission, name).await?;
+        client.sync_devices().await?;
+        Ok(uploaded)
+    }
+
+    #[tokio::test]
+    async fn test_stub_client() {
+        let client: Box<dyn LitchiApiClient> = Box::<StubClient>::default();
+        let mission = LitchiMission::default();
+
+        replace_mission(client.as_ref(), &mission, "survey")
+            .await
+            .unwrap();
+        let uploaded = replace_mission(client.as_ref(), &mission, "survey")
+            .await
+            .unwrap();
+
+        let missions = client.missions().await.unwrap();
+        assert_eq!(missions.len(), 1);
+        assert_eq!(missions[0].object_id, uploaded);
+        assert_eq!(
+            client.download_mission(&missions[0]).await.unwrap(),
+            mission
+        );
+    }
+}
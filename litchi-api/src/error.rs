@@ -1,15 +1,137 @@
+use std::fmt;
+
+use litchitool::error::LitchiError;
+use serde::Deserialize;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum LitchiApiError {
     #[error("Http error: {0:?}")]
-    ReqwestError(#[from] reqwest::Error),
+    ReqwestError(reqwest::Error),
+    /// The server didn't answer within the timeouts of the client
+    #[error("Request timed out: {0}")]
+    Timeout(reqwest::Error),
+    #[error("Refusing to talk to {0} without HTTPS")]
+    InsecureBaseUrl(String),
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
     #[error("Authentication error: {0}")]
-    AuthError(String),
-    #[error("HTTP error (code: {0}): {1}")]
-    HTTPError(u16, String),
-    #[error("Invalid mission JSON format: {0}")]
+    AuthError(ParseError),
+    #[error("API error: {0}")]
+    ParseError(#[from] ParseError),
+    #[error("Mission error: {0}")]
+    Mission(#[from] LitchiError),
+    /// A mission object or mission file as stored in the cloud couldn't be read
+    #[error("Invalid mission format: {0}")]
     MissionFormatError(String),
     #[error("Response format error: {0} ({1})")]
     ResponseFormateError(String, String),
+    /// The user already has a mission of that name, see [`crate::LitchiApi::upload_unique`]
+    #[error("A mission named {0:?} already exists")]
+    DuplicateMissionName(String),
+}
+
+impl From<reqwest::Error> for LitchiApiError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            LitchiApiError::Timeout(error)
+        } else {
+            LitchiApiError::ReqwestError(error)
+        }
+    }
+}
+
+/// Error returned by the Parse backend, usually a `{"code": N, "error": "message"}` body
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub struct ParseError {
+    pub http_status: u16,
+    /// Parse error code, `None` if the body wasn't a Parse error
+    pub parse_code: Option<i32>,
+    /// Parse error message, or the raw body if it couldn't be parsed
+    pub message: String,
+}
+
+impl ParseError {
+    pub const OBJECT_NOT_FOUND: i32 = 101;
+    pub const INVALID_JSON: i32 = 107;
+    pub const INVALID_SESSION_TOKEN: i32 = 209;
+    pub const USERNAME_TAKEN: i32 = 202;
+
+    pub fn from_body(http_status: u16, body: &str) -> Self {
+        #[derive(Deserialize)]
+        struct ParseErrorBody {
+            code: i32,
+            error: String,
+        }
+
+        match serde_json::from_str::<ParseErrorBody>(body) {
+            Ok(error) => Self {
+                http_status,
+                parse_code: Some(error.code),
+                message: error.error,
+            },
+            Err(_) => Self {
+                http_status,
+                parse_code: None,
+                message: body.to_string(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.parse_code {
+            Some(code) => write!(
+                f,
+                "{} (HTTP {}, Parse code {code})",
+                self.message, self.http_status
+            ),
+            None => write!(f, "{} (HTTP {})", self.message, self.http_status),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_error_codes() {
+        let cases = [
+            (404, r#"{"code":101,"error":"Object not found."}"#, 101),
+            (
+                400,
+                r#"{"code":202,"error":"Account already exists for this username."}"#,
+                202,
+            ),
+            (400, r#"{"code":209,"error":"Invalid session token"}"#, 209),
+        ];
+
+        for (status, body, code) in cases {
+            let error = ParseError::from_body(status, body);
+            assert_eq!(error.http_status, status);
+            assert_eq!(error.parse_code, Some(code));
+        }
+
+        assert_eq!(
+            ParseError::from_body(404, r#"{"code":101,"error":"Object not found."}"#).message,
+            "Object not found."
+        );
+    }
+
+    #[test]
+    fn test_parse_error_raw_fallback() {
+        let error = ParseError::from_body(502, "<html>Bad Gateway</html>");
+
+        assert_eq!(error.parse_code, None);
+        assert_eq!(error.message, "<html>Bad Gateway</html>");
+        assert!(matches!(
+            LitchiApiError::from(error),
+            LitchiApiError::ParseError(ParseError {
+                http_status: 502,
+                ..
+            })
+        ));
+    }
 }
@@ -1,51 +1,257 @@
+mod client;
 pub mod error;
+pub mod transport;
 mod types;
 
+pub use client::LitchiApiClient;
 pub use types::*;
 
-use error::LitchiApiError;
+use bytes::Bytes;
+use error::{LitchiApiError, ParseError};
 use litchitool::mission::LitchiMission;
 use reqwest::{
     header::{HeaderMap, HeaderName, HeaderValue},
-    Client, Response,
+    redirect::Policy,
+    Client, Proxy,
 };
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tracing::{debug, instrument, trace};
+use tracing::{debug, instrument, trace, warn};
+use transport::{HttpRequest, HttpResponse, HttpTransport, ReqwestTransport};
 
 const APP_ID: &str = "APjd97yuFQ9TUiIIKgDiqzczon1z2339RxINQe6g";
 static API_BASE: &str = "https://parse.litchiapi.com";
 
-pub struct LitchiApi {
-    client: Client,
+/// Connection settings for the Litchi Parse backend
+#[derive(Debug, Clone)]
+pub struct LitchiApiConfig {
+    /// Base URL of the Parse server, without a trailing slash
+    pub base_url: String,
+    /// HTTP or SOCKS proxy all requests are sent through. When unset, the standard
+    /// `HTTP_PROXY`/`HTTPS_PROXY`/`ALL_PROXY` environment variables are honored.
+    pub proxy: Option<Proxy>,
+    /// Allows a plain `http://` base URL, for testing against a local server. Credentials and
+    /// session tokens are then sent unencrypted.
+    pub allow_insecure: bool,
+}
+
+/// How requests failing with a transient error are retried. The delay before each retry starts
+/// at `initial_backoff` and doubles with every attempt, up to `max_backoff`.
+///
+/// Requests creating something, like uploads, are only retried when they can't have reached the
+/// backend (connection errors, 502 and 503), never after a timeout, since they may have
+/// succeeded on the server and sending them again would duplicate the result.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Attempts of a request in total, `1` disables retrying
+    pub max_attempts: usize,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+/// Settings of the client created by [`LitchiApi::login_with_options`]
+#[derive(Debug, Clone)]
+pub struct ClientOptions {
+    pub config: LitchiApiConfig,
+    pub retry: RetryPolicy,
+    /// Time a single request may take in total, from connecting until the response body is read
+    pub timeout: Duration,
+    /// Time establishing the connection to the server may take
+    pub connect_timeout: Duration,
+}
+
+pub struct LitchiApi<T = ReqwestTransport> {
+    transport: T,
+    base_url: String,
     session_data: SessionData,
+    retry_policy: RetryPolicy,
+    /// Last result of [`LitchiApi::missions_cached`] and when it was fetched
+    mission_cache: Mutex<Option<(Instant, Vec<Mission>)>>,
+}
+
+impl Default for LitchiApiConfig {
+    fn default() -> Self {
+        Self {
+            base_url: API_BASE.to_string(),
+            proxy: None,
+            allow_insecure: false,
+        }
+    }
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            config: LitchiApiConfig::default(),
+            retry: RetryPolicy::default(),
+            timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl From<LitchiApiConfig> for ClientOptions {
+    fn from(config: LitchiApiConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before sending the request again after the failed `attempt`, counting from 1
+    pub fn backoff(&self, attempt: usize) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1) as u32);
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
 }
 
 impl LitchiApi {
     pub async fn login(username: &str, password: &str) -> Result<Self, LitchiApiError> {
-        let mut client = Client::builder()
-            .default_headers(HeaderMap::from_iter([(
-                HeaderName::from_static("x-parse-application-id"),
-                HeaderValue::from_static(APP_ID),
-            )]))
-            .cookie_store(true)
-            .build()?;
+        Self::login_with_config(&LitchiApiConfig::default(), username, password).await
+    }
 
-        let session_data = Self::authenticate(&mut client, username, password).await?;
+    pub async fn login_with_config(
+        config: &LitchiApiConfig,
+        username: &str,
+        password: &str,
+    ) -> Result<Self, LitchiApiError> {
+        Self::login_with_options(username, password, config.clone().into()).await
+    }
+
+    /// Logs in with the given connection settings, retry policy and timeouts
+    pub async fn login_with_options(
+        username: &str,
+        password: &str,
+        options: ClientOptions,
+    ) -> Result<Self, LitchiApiError> {
+        let transport = ReqwestTransport(build_client(&options)?);
+
+        Ok(
+            Self::login_with_transport(transport, &options.config.base_url, username, password)
+                .await?
+                .with_retry_policy(options.retry),
+        )
+    }
+
+    /// Resumes a session of an earlier login, see [`LitchiApi::from_session_with_transport`]
+    pub async fn from_session(session_data: SessionData) -> Result<Self, LitchiApiError> {
+        Self::from_session_with_config(&LitchiApiConfig::default(), session_data).await
+    }
+
+    pub async fn from_session_with_config(
+        config: &LitchiApiConfig,
+        session_data: SessionData,
+    ) -> Result<Self, LitchiApiError> {
+        Self::from_session_with_options(session_data, config.clone().into()).await
+    }
+
+    pub async fn from_session_with_options(
+        session_data: SessionData,
+        options: ClientOptions,
+    ) -> Result<Self, LitchiApiError> {
+        let transport = ReqwestTransport(build_client(&options)?);
+
+        Ok(
+            Self::from_session_with_transport(transport, &options.config.base_url, session_data)
+                .await?
+                .with_retry_policy(options.retry),
+        )
+    }
+
+    /// Checks that the Parse backend is reachable, without logging in
+    #[instrument(err)]
+    pub async fn ping(config: &LitchiApiConfig) -> Result<(), LitchiApiError> {
+        let transport = ReqwestTransport(build_client(&config.clone().into())?);
+
+        debug!("Checking backend health");
+        let response = transport
+            .execute(HttpRequest::get(config.base_url.clone() + "/parse/health"))
+            .await?;
+
+        check_api_response(response)?;
+
+        Ok(())
+    }
+}
+
+impl<T: HttpTransport> LitchiApi<T> {
+    /// Logs in, sending all requests through the given transport
+    pub async fn login_with_transport(
+        transport: T,
+        base_url: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<Self, LitchiApiError> {
+        let session_data = Self::authenticate(&transport, base_url, username, password).await?;
 
         Ok(Self {
-            client,
+            transport,
+            base_url: base_url.to_string(),
             session_data,
+            retry_policy: RetryPolicy::default(),
+            mission_cache: Mutex::new(None),
         })
     }
 
-    #[instrument(skip(password, client), err)]
+    /// Resumes a session of an earlier login instead of logging in again. The session token is
+    /// checked by reading the user, failing with [`LitchiApiError::AuthError`] if it expired.
+    #[instrument(skip_all, fields(username = session_data.username), err)]
+    pub async fn from_session_with_transport(
+        transport: T,
+        base_url: &str,
+        session_data: SessionData,
+    ) -> Result<Self, LitchiApiError> {
+        debug!("Checking session token");
+        let response = transport
+            .execute(
+                HttpRequest::get(base_url.to_string() + "/parse/users/me")
+                    .header("X-Parse-Session-Token", &session_data.session_token),
+            )
+            .await?;
+
+        match check_api_response(response) {
+            Ok(_) => Ok(Self {
+                transport,
+                base_url: base_url.to_string(),
+                session_data,
+                retry_policy: RetryPolicy::default(),
+                mission_cache: Mutex::new(None),
+            }),
+            Err(LitchiApiError::ParseError(error)) => Err(LitchiApiError::AuthError(error)),
+            Err(error) => Err(error),
+        }
+    }
+
+    #[instrument(skip(password, transport), err)]
     async fn authenticate(
-        client: &mut Client,
+        transport: &T,
+        base_url: &str,
         username: &str,
         password: &str,
     ) -> Result<SessionData, LitchiApiError> {
-        let url = API_BASE.to_string() + "/parse/login";
+        let url = base_url.to_string() + "/parse/login";
 
         #[derive(Serialize)]
         struct LoginPayload<'a> {
@@ -53,126 +259,401 @@ impl LitchiApi {
             password: &'a str,
         }
 
-        let result = client
-            .post(url)
-            .json(&LoginPayload { username, password })
-            .send()
+        let result = transport
+            .execute(HttpRequest::post(url).json(&LoginPayload { username, password })?)
             .await?;
 
-        if !result.status().is_success() {
-            Err(LitchiApiError::AuthError(result.text().await?))
-        } else {
-            Ok(result.json().await?)
+        match check_api_response(result) {
+            Ok(result) => Ok(result.json()?),
+            Err(LitchiApiError::ParseError(error)) => Err(LitchiApiError::AuthError(error)),
+            Err(error) => Err(error),
         }
     }
 
+    /// Ends the session, invalidating its token on the server. Further requests need a new
+    /// [`LitchiApi::login`].
+    #[instrument(skip(self), err)]
+    pub async fn logout(self) -> Result<(), LitchiApiError> {
+        debug!("Logging out");
+        let response = self
+            .transport
+            .execute(
+                HttpRequest::post(self.url("/parse/logout"))
+                    .header("X-Parse-Session-Token", &self.session_data.session_token),
+            )
+            .await?;
+
+        check_api_response(response)?;
+
+        Ok(())
+    }
+
+    /// Replaces the [`RetryPolicy`] of the requests sent from now on
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
     pub fn user_data(&self) -> &SessionData {
         &self.session_data
     }
 
-    #[instrument(skip_all, fields(mission_name = %name), err)]
+    fn url(&self, path: &str) -> String {
+        self.base_url.clone() + path
+    }
+
     pub async fn upload(
         &self,
         mission: &LitchiMission,
         name: &str,
     ) -> Result<ObjectId, LitchiApiError> {
-        let url = API_BASE.to_string() + "/parse/files/mission";
+        self.upload_with_options(mission, name, &UploadOptions::default())
+            .await
+    }
 
-        #[derive(Deserialize)]
-        struct UploadResult {
-            name: String,
-            url: String,
+    /// Like [`LitchiApi::upload`], but fails with [`LitchiApiError::DuplicateMissionName`]
+    /// instead of creating a second mission with the same name. The check looks up the name
+    /// right before uploading, a mission created elsewhere in between isn't noticed.
+    pub async fn upload_unique(
+        &self,
+        mission: &LitchiMission,
+        name: &str,
+    ) -> Result<ObjectId, LitchiApiError> {
+        if !self.missions_named(name).await?.is_empty() {
+            Err(LitchiApiError::DuplicateMissionName(name.to_string()))?;
         }
 
-        trace!("Converting mission to binary");
-        let mission_bin = mission.to_binary();
-        debug!("Uploading mission binary blob to litchi");
-        let mission_file = self
-            .client
-            .post(url)
-            .header("Content-Type", "application/octet-stream")
-            .header("X-Parse-Session-Token", &self.session_data.session_token)
-            .body(mission_bin)
-            .send()
-            .await?;
-        trace!("Parsing mission data upload result");
-        let mission_file: UploadResult = mission_file.json().await?;
+        self.upload(mission, name).await
+    }
+
+    /// Like [`LitchiApi::upload_unique`], but replaces the missions with the same name instead
+    /// of failing. They are only deleted once the new mission is uploaded, so a failed upload
+    /// keeps them. The new mission gets a new object id, see [`LitchiApi::update_mission`] to
+    /// keep it.
+    pub async fn upload_overwriting(
+        &self,
+        mission: &LitchiMission,
+        name: &str,
+    ) -> Result<ObjectId, LitchiApiError> {
+        let uploaded = self.upload(mission, name).await?;
+
+        for existing in self.missions_named(name).await? {
+            if existing.object_id != uploaded {
+                debug!(
+                    object_id = existing.object_id.0,
+                    "Deleting mission of the same name"
+                );
+                self.delete_mission(existing.object_id).await?;
+            }
+        }
 
-        let (mission_lat, mission_long) =
-            mission.waypoints().get(0).map_or((0.0, 0.0), |waypoint| {
-                (waypoint.coordinate.0, waypoint.coordinate.1)
-            });
+        Ok(uploaded)
+    }
+
+    /// Uploads the mission file, then creates the mission object referencing it.
+    ///
+    /// The Parse file endpoint takes the whole file in a single request and can't resume a
+    /// partial upload, and removing stored files requires the master key. So instead of resuming,
+    /// each step is retried on its own, as far as it can't have reached the server (see
+    /// [`RetryPolicy`]): a failed file upload is sent again, while a failed object creation reuses
+    /// the already stored file rather than uploading (and orphaning) another copy, and first looks
+    /// for an object the failed attempt may have created anyway.
+    pub async fn upload_with_options(
+        &self,
+        mission: &LitchiMission,
+        name: &str,
+        options: &UploadOptions,
+    ) -> Result<ObjectId, LitchiApiError> {
+        Ok(self
+            .upload_with_receipt(mission, name, options)
+            .await?
+            .object_id)
+    }
+
+    /// Like [`LitchiApi::upload_with_options`], but also reports the size of the uploaded file,
+    /// where it is stored and how long the upload took
+    #[instrument(skip_all, fields(mission_name = %name), err)]
+    pub async fn upload_with_receipt(
+        &self,
+        mission: &LitchiMission,
+        name: &str,
+        options: &UploadOptions,
+    ) -> Result<UploadReceipt, LitchiApiError> {
+        let started = Instant::now();
+
+        mission.validate()?;
+
+        trace!("Converting mission to binary");
+        let mission_bin = mission.try_to_binary()?;
+        let mission_file = self.upload_file(&mission_bin).await?;
 
-        let upload_payload = json!({
+        let mut upload_payload = json!({
             "ACL": {
                 &self.session_data.object_id: {
                     "read": true,
                     "write": true,
                 }
             },
-            "location": {
-                "__type": "GeoPoint",
-                "latitude": mission_lat,
-                "longitude": mission_long,
-            },
+            "location": location(mission),
             "name": name,
             "user": {
                 "__type": "Pointer",
                 "className": "_User",
                 "objectId": self.session_data.object_id,
             },
-            "file": {
-                "__type": "File",
-                "name": mission_file.name,
-                "url": mission_file.url,
+            "file": mission_file.pointer(),
+        });
+
+        let payload_fields = upload_payload
+            .as_object_mut()
+            .expect("Upload payload is an object");
+        for (key, value) in &options.metadata {
+            if payload_fields.contains_key(key) || MISSION_FIELDS.contains(&key.as_str()) {
+                warn!(
+                    key,
+                    "Ignoring metadata field reserved for the mission itself"
+                );
+            } else {
+                payload_fields.insert(key.clone(), value.clone());
             }
+        }
+
+        // The file is stored at this point, retries only recreate the object pointing to it. An
+        // attempt that failed may still have created it, so look for it before creating another.
+        let attempted = AtomicBool::new(false);
+        let object_id = self
+            .retry_transient("mission object creation", false, || async {
+                if attempted.swap(true, Ordering::Relaxed) {
+                    if let Some(object_id) = self.find_mission_by_file(&mission_file.name).await? {
+                        debug!(
+                            object_id = object_id.0,
+                            "Mission object was already created"
+                        );
+                        return Ok(object_id);
+                    }
+                }
+
+                debug!("Creating mission object");
+                let response = self
+                    .transport
+                    .execute(
+                        HttpRequest::post(self.url("/parse/classes/Mission"))
+                            .header("X-Parse-Session-Token", &self.session_data.session_token)
+                            .json(&upload_payload)?,
+                    )
+                    .await?;
+                let response: serde_json::Value = check_api_response(response)?.json()?;
+
+                trace!("Parsing mission object creation response");
+                response
+                    .get("objectId")
+                    .and_then(|object_id| Some(ObjectId(object_id.as_str()?.to_string())))
+                    .ok_or(LitchiApiError::ResponseFormateError(
+                        "Response has no objectId".to_string(),
+                        response.to_string(),
+                    ))
+            })
+            .await?;
+
+        self.invalidate_mission_cache();
+
+        let receipt = UploadReceipt {
+            object_id,
+            bytes_uploaded: mission_bin.len(),
+            duration: started.elapsed(),
+            file_url: mission_file.url,
+        };
+        debug!(
+            bytes = receipt.bytes_uploaded,
+            duration = ?receipt.duration,
+            "Uploaded mission"
+        );
+        Ok(receipt)
+    }
+
+    /// Replaces the file of an existing mission, keeping its object id, owner and ACL. The
+    /// location moves to the first waypoint of the new file, the name only changes if given.
+    ///
+    /// The previous file stays stored on the server, like it does after deleting a mission.
+    #[instrument(skip(self, mission), err)]
+    pub async fn update_mission(
+        &self,
+        mission_id: &ObjectId,
+        mission: &LitchiMission,
+        name: Option<&str>,
+    ) -> Result<(), LitchiApiError> {
+        mission.validate()?;
+
+        trace!("Converting mission to binary");
+        let mission_bin = mission.try_to_binary()?;
+        let mission_file = self.upload_file(&mission_bin).await?;
+
+        let mut update_payload = json!({
+            "location": location(mission),
+            "file": mission_file.pointer(),
         });
+        if let Some(name) = name {
+            update_payload["name"] = json!(name);
+        }
 
-        debug!("Creating mission object");
-        let create_mission_response: serde_json::Value = check_api_response(
-            self.client
-                .post(API_BASE.to_string() + "/parse/classes/Mission")
-                .header("X-Parse-Session-Token", &self.session_data.session_token)
-                .json(&upload_payload)
-                .send()
-                .await?,
-        )
-        .await?
-        .json()
-        .await?;
+        let result = self
+            .retry_transient("mission object update", true, || async {
+                debug!("Updating mission object");
+                let response = self
+                    .transport
+                    .execute(
+                        HttpRequest::put(
+                            self.url(&format!("/parse/classes/Mission/{}", mission_id.0)),
+                        )
+                        .header("X-Parse-Session-Token", &self.session_data.session_token)
+                        .json(&update_payload)?,
+                    )
+                    .await?;
+                check_api_response(response)
+            })
+            .await;
 
-        trace!("Parsing mission object creation response");
-        create_mission_response
-            .get("objectId")
-            .and_then(|object_id| Some(ObjectId(object_id.as_str()?.to_string())))
-            .ok_or(LitchiApiError::ResponseFormateError(
-                "Response has no objectId".to_string(),
-                create_mission_response.to_string(),
-            ))
+        self.invalidate_mission_cache();
+        result?;
+
+        Ok(())
     }
 
-    #[instrument(skip(self), err)]
-    pub async fn missions(&self) -> Result<Vec<Mission>, LitchiApiError> {
+    /// Stores the mission binary as a Parse file, retrying on transient failures
+    async fn upload_file(&self, mission_bin: &Bytes) -> Result<StoredFile, LitchiApiError> {
+        let url = self.url("/parse/files/mission");
+
+        self.retry_transient("mission file upload", false, || async {
+            debug!("Uploading mission binary blob to litchi");
+            let mission_file = self
+                .transport
+                .execute(
+                    HttpRequest::post(&url)
+                        .header("Content-Type", "application/octet-stream")
+                        .header("X-Parse-Session-Token", &self.session_data.session_token)
+                        .body(mission_bin.to_vec()),
+                )
+                .await?;
+            trace!("Parsing mission data upload result");
+            check_api_response(mission_file)?.json()
+        })
+        .await
+    }
+
+    /// The mission of the user referencing the stored file, if there is one
+    async fn find_mission_by_file(
+        &self,
+        file_name: &str,
+    ) -> Result<Option<ObjectId>, LitchiApiError> {
         let payload = json!({
            "where": {
                 "user": {
                     "__type": "Pointer",
                     "className": "_User",
                     "objectId": self.session_data.object_id,
+                },
+                "file": {
+                    "__type": "File",
+                    "name": file_name,
                 }
             }
         });
-        debug!("Requesting misssions");
-        let response = self
-            .client
-            .get(API_BASE.to_string() + "/parse/classes/Mission")
-            .header("X-Parse-Session-Token", &self.session_data.session_token)
-            .json(&payload)
-            .send()
+        let response: serde_json::Value = self
+            .retry_transient("mission lookup", true, || async {
+                debug!(file_name, "Looking up mission by file");
+                let response = self
+                    .transport
+                    .execute(
+                        HttpRequest::get(self.url("/parse/classes/Mission?limit=1"))
+                            .header("X-Parse-Session-Token", &self.session_data.session_token)
+                            .json(&payload)?,
+                    )
+                    .await?;
+                check_api_response(response)?.json()
+            })
             .await?;
 
-        // Check status
-        let response: serde_json::Value = check_api_response(response).await?.json().await?;
+        Ok(response
+            .get("results")
+            .and_then(|results| results.get(0)?.get("objectId")?.as_str())
+            .map(|object_id| ObjectId(object_id.to_string())))
+    }
+
+    /// Lists all missions of the user, requesting them in pages of [`MISSIONS_PAGE_SIZE`]
+    #[instrument(skip(self), err)]
+    pub async fn missions(&self) -> Result<Vec<Mission>, LitchiApiError> {
+        let mut missions = vec![];
+        loop {
+            let page = self
+                .missions_paged(MISSIONS_PAGE_SIZE, missions.len())
+                .await?;
+            let last_page = page.len() < MISSIONS_PAGE_SIZE;
+            missions.extend(page);
+            if last_page {
+                return Ok(missions);
+            }
+        }
+    }
+
+    /// Lists at most `limit` missions of the user, skipping the first `skip` ones. The missions
+    /// are ordered by creation, so pages stay consistent while missions are only added.
+    #[instrument(skip(self), err)]
+    pub async fn missions_paged(
+        &self,
+        limit: usize,
+        skip: usize,
+    ) -> Result<Vec<Mission>, LitchiApiError> {
+        self.query_missions(json!({}), limit, skip).await
+    }
+
+    /// The missions of the user with the given name, letting the server filter them rather
+    /// than listing all missions
+    async fn missions_named(&self, name: &str) -> Result<Vec<Mission>, LitchiApiError> {
+        let mut missions = vec![];
+        loop {
+            let page = self
+                .query_missions(json!({ "name": name }), MISSIONS_PAGE_SIZE, missions.len())
+                .await?;
+            let last_page = page.len() < MISSIONS_PAGE_SIZE;
+            missions.extend(page);
+            if last_page {
+                return Ok(missions);
+            }
+        }
+    }
+
+    /// Requests a page of the user's missions matching the `where` constraints of `filter`,
+    /// ordered by creation time and object id, as Parse doesn't guarantee an order otherwise
+    async fn query_missions(
+        &self,
+        mut filter: serde_json::Value,
+        limit: usize,
+        skip: usize,
+    ) -> Result<Vec<Mission>, LitchiApiError> {
+        filter["user"] = json!({
+            "__type": "Pointer",
+            "className": "_User",
+            "objectId": self.session_data.object_id,
+        });
+        let payload = json!({ "where": filter });
+        let response: serde_json::Value = self
+            .retry_transient("mission listing", true, || async {
+                debug!("Requesting misssions");
+                let response = self
+                    .transport
+                    .execute(
+                        HttpRequest::get(
+                            self.url(&format!(
+                                "/parse/classes/Mission?limit={limit}&skip={skip}&order=createdAt,objectId"
+                            )),
+                        )
+                        .header("X-Parse-Session-Token", &self.session_data.session_token)
+                        .json(&payload)?,
+                    )
+                    .await?;
+                check_api_response(response)?.json()
+            })
+            .await?;
 
         response
             .get("results")
@@ -185,50 +666,314 @@ impl LitchiApi {
                 results
                     .iter()
                     .map(|result| result.try_into())
-                    .collect::<Result<_, _>>()
+                    .collect::<Result<Vec<Mission>, _>>()
+            })
+            .inspect(|missions| {
+                for mission in missions.iter().filter(|mission| mission.is_null_island()) {
+                    warn!(
+                        object_id = mission.object_id.0,
+                        name = mission.name,
+                        "Mission is located at (0, 0), it was probably uploaded without waypoints"
+                    );
+                }
+            })
+    }
+
+    /// Like [`LitchiApi::missions`], but answers from the last result if it is less than `ttl`
+    /// old. [`LitchiApi::upload`] and [`LitchiApi::delete_mission`] invalidate the cache, changes
+    /// made elsewhere only show up once it expired.
+    pub async fn missions_cached(&self, ttl: Duration) -> Result<Vec<Mission>, LitchiApiError> {
+        if let Some((fetched, missions)) = &*self.mission_cache.lock().expect("Cache lock poisoned")
+        {
+            if fetched.elapsed() < ttl {
+                trace!("Answering missions from cache");
+                return Ok(missions.clone());
+            }
+        }
+
+        let missions = self.missions().await?;
+        *self.mission_cache.lock().expect("Cache lock poisoned") =
+            Some((Instant::now(), missions.clone()));
+
+        Ok(missions)
+    }
+
+    /// Makes the next [`LitchiApi::missions_cached`] call fetch the missions again
+    pub fn invalidate_mission_cache(&self) {
+        *self.mission_cache.lock().expect("Cache lock poisoned") = None;
+    }
+
+    #[instrument(skip(self), err)]
+    pub async fn rename_mission(
+        &self,
+        mission_id: &ObjectId,
+        new_name: &str,
+    ) -> Result<(), LitchiApiError> {
+        let result = self
+            .retry_transient("mission rename", true, || async {
+                debug!("Requesting to rename mission");
+                let response = self
+                    .transport
+                    .execute(
+                        HttpRequest::put(
+                            self.url(&format!("/parse/classes/Mission/{}", mission_id.0)),
+                        )
+                        .header("X-Parse-Session-Token", &self.session_data.session_token)
+                        .json(&json!({ "name": new_name }))?,
+                    )
+                    .await?;
+                check_api_response(response)
             })
+            .await;
+
+        self.invalidate_mission_cache();
+        result?;
+
+        Ok(())
     }
 
     #[instrument(skip(self), err)]
     pub async fn delete_mission(&self, mission_id: ObjectId) -> Result<(), LitchiApiError> {
-        debug!("Requesting to delete mission");
+        let result = self
+            .retry_transient("mission deletion", true, || async {
+                debug!("Requesting to delete mission");
+                let response = self
+                    .transport
+                    .execute(
+                        HttpRequest::delete(
+                            self.url(&format!("/parse/classes/Mission/{}", mission_id.0)),
+                        )
+                        .header("X-Parse-Session-Token", &self.session_data.session_token),
+                    )
+                    .await?;
+                check_api_response(response)
+            })
+            .await;
+
+        self.invalidate_mission_cache();
+        result?;
+
+        Ok(())
+    }
+
+    /// Downloads the mission file exactly as it is stored, without parsing or re-encoding it
+    #[instrument(skip(self, mission), fields(object_id = mission.object_id.0), err)]
+    pub async fn download_mission_bytes(&self, mission: &Mission) -> Result<Bytes, LitchiApiError> {
+        debug!(url = mission.file.url, "Downloading mission file");
         let response = self
-            .client
-            .delete(format!(
-                "{}/parse/classes/Mission/{}",
-                API_BASE, mission_id.0
-            ))
-            .header("X-Parse-Session-Token", &self.session_data.session_token)
-            .send()
+            .transport
+            .execute(HttpRequest::get(&mission.file.url))
             .await?;
 
-        check_api_response(response).await?;
+        Ok(check_api_response(response)?.body.into())
+    }
 
-        Ok(())
+    /// Downloads and parses the mission file, see [`LitchiApi::download_mission_bytes`] for the
+    /// file as is
+    pub async fn download_mission(
+        &self,
+        mission: &Mission,
+    ) -> Result<LitchiMission, LitchiApiError> {
+        let bytes = self.download_mission_bytes(mission).await?;
+
+        LitchiMission::from_binary(&bytes).map_err(|error| {
+            LitchiApiError::MissionFormatError(format!(
+                "mission file of {} can't be parsed: {error}",
+                mission.object_id.0
+            ))
+        })
     }
 
+    /// Lists the devices of the user, which receive the missions on [`LitchiApi::sync_devices`]
     #[instrument(skip(self), err)]
-    pub async fn sync_devices(&self) -> Result<(), LitchiApiError> {
-        debug!("Synchronizing devices");
-        let response = self
-            .client
-            .post(API_BASE.to_string() + "/parse/functions/syncMyDevices")
-            .header("X-Parse-Session-Token", &self.session_data.session_token)
-            .send()
+    pub async fn devices(&self) -> Result<Vec<Device>, LitchiApiError> {
+        let payload = json!({
+           "where": {
+                "user": {
+                    "__type": "Pointer",
+                    "className": "_User",
+                    "objectId": self.session_data.object_id,
+                }
+            }
+        });
+        let response: serde_json::Value = self
+            .retry_transient("device listing", true, || async {
+                debug!("Requesting devices");
+                let response = self
+                    .transport
+                    .execute(
+                        HttpRequest::get(self.url("/parse/classes/Device"))
+                            .header("X-Parse-Session-Token", &self.session_data.session_token)
+                            .json(&payload)?,
+                    )
+                    .await?;
+                check_api_response(response)?.json()
+            })
             .await?;
 
-        check_api_response(response).await?;
+        response
+            .get("results")
+            .and_then(|results| results.as_array())
+            .ok_or(LitchiApiError::ResponseFormateError(
+                "response should have results array field".to_string(),
+                response.to_string(),
+            ))?
+            .iter()
+            .map(Device::try_from)
+            .collect()
+    }
+
+    #[instrument(skip(self), err)]
+    pub async fn sync_devices(&self) -> Result<(), LitchiApiError> {
+        self.retry_transient("device synchronization", false, || async {
+            debug!("Synchronizing devices");
+            let response = self
+                .transport
+                .execute(
+                    HttpRequest::post(self.url("/parse/functions/syncMyDevices"))
+                        .header("X-Parse-Session-Token", &self.session_data.session_token),
+                )
+                .await?;
+            check_api_response(response)
+        })
+        .await?;
 
         Ok(())
     }
+
+    /// Sends the request until it succeeds, fails with an error that isn't transient or the
+    /// attempts of the [`RetryPolicy`] are used up, waiting with backoff between attempts. Requests
+    /// that aren't `idempotent` are only sent again if they can't have reached the server, see
+    /// [`is_transient`].
+    async fn retry_transient<R, F, Fut>(
+        &self,
+        operation: &str,
+        idempotent: bool,
+        mut request: F,
+    ) -> Result<R, LitchiApiError>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<R, LitchiApiError>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match request().await {
+                Err(error)
+                    if attempt < self.retry_policy.max_attempts
+                        && is_transient(&error, idempotent) =>
+                {
+                    let backoff = self.retry_policy.backoff(attempt);
+                    warn!(operation, attempt, ?backoff, %error, "Retrying after transient error");
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// A file stored by the Parse file endpoint
+#[derive(Deserialize)]
+struct StoredFile {
+    name: String,
+    url: String,
+}
+
+impl StoredFile {
+    /// The file field of a mission object referencing this file
+    fn pointer(&self) -> serde_json::Value {
+        json!({
+            "__type": "File",
+            "name": self.name,
+            "url": self.url,
+        })
+    }
 }
 
-async fn check_api_response(response: Response) -> Result<Response, LitchiApiError> {
-    if !response.status().is_success() {
-        Err(LitchiApiError::HTTPError(
-            response.status().as_u16(),
-            response.text().await?,
-        ))
+/// The location field of a mission object, its first waypoint or (0, 0) without waypoints
+fn location(mission: &LitchiMission) -> serde_json::Value {
+    let (latitude, longitude) = mission.waypoints().first().map_or((0.0, 0.0), |waypoint| {
+        (waypoint.coordinate.0, waypoint.coordinate.1)
+    });
+
+    json!({
+        "__type": "GeoPoint",
+        "latitude": latitude,
+        "longitude": longitude,
+    })
+}
+
+/// Missions requested at once by [`LitchiApi::missions`], the default limit of Parse
+pub const MISSIONS_PAGE_SIZE: usize = 100;
+
+/// Whether the request may succeed when sent again. Client errors, including failed
+/// authentication, are never retried.
+///
+/// A request that isn't `idempotent` (like creating an object) may have succeeded on the server
+/// although it timed out or failed with a server error while processing it, so it is only
+/// retried if it never reached the backend: on connection errors, and on 502 and 503 responses
+/// of the proxy in front of it.
+fn is_transient(error: &LitchiApiError, idempotent: bool) -> bool {
+    match error {
+        LitchiApiError::Timeout(_) => idempotent,
+        // Timeouts are mapped to `Timeout` when converting
+        LitchiApiError::ReqwestError(error) => error.is_connect(),
+        LitchiApiError::ParseError(error) if idempotent => error.http_status >= 500,
+        LitchiApiError::ParseError(error) => matches!(error.http_status, 502 | 503),
+        _ => false,
+    }
+}
+
+/// Redirects followed by the client before giving up
+const MAX_REDIRECTS: usize = 10;
+
+/// Builds the client for the configured server. Redirects are only followed to the same origin,
+/// so requests carrying credentials can't be sent to another host or downgraded to plain HTTP.
+fn build_client(options: &ClientOptions) -> Result<Client, LitchiApiError> {
+    let config = &options.config;
+    if !config.allow_insecure && !config.base_url.starts_with("https://") {
+        Err(LitchiApiError::InsecureBaseUrl(config.base_url.clone()))?;
+    }
+
+    let redirect_policy = Policy::custom(|attempt| {
+        let origin = &attempt.previous()[0];
+        let target = attempt.url();
+        let same_origin = target.scheme() == origin.scheme()
+            && target.host_str() == origin.host_str()
+            && target.port_or_known_default() == origin.port_or_known_default();
+
+        if !same_origin {
+            let error = format!("Refusing to follow redirect from {origin} to {target}");
+            attempt.error(error)
+        } else if attempt.previous().len() > MAX_REDIRECTS {
+            attempt.error("Too many redirects")
+        } else {
+            attempt.follow()
+        }
+    });
+
+    let mut builder = Client::builder()
+        .redirect(redirect_policy)
+        .timeout(options.timeout)
+        .connect_timeout(options.connect_timeout)
+        .default_headers(HeaderMap::from_iter([(
+            HeaderName::from_static("x-parse-application-id"),
+            HeaderValue::from_static(APP_ID),
+        )]))
+        .cookie_store(true);
+
+    // Without an explicit proxy reqwest falls back to the proxy environment variables
+    if let Some(proxy) = &config.proxy {
+        builder = builder.proxy(proxy.clone());
+    }
+
+    Ok(builder.build()?)
+}
+
+fn check_api_response(response: HttpResponse) -> Result<HttpResponse, LitchiApiError> {
+    if !response.is_success() {
+        Err(ParseError::from_body(response.status, &response.text()))?
     } else {
         Ok(response)
     }
@@ -236,19 +981,1038 @@ async fn check_api_response(response: Response) -> Result<Response, LitchiApiErr
 
 #[cfg(test)]
 mod tests {
-    use crate::{error::LitchiApiError, LitchiApi};
+    use std::{collections::VecDeque, sync::Mutex, time::Duration};
+
+    use wiremock::{matchers, Mock, MockServer, ResponseTemplate};
+
+    use litchitool::{
+        error::LitchiError,
+        mission::{Coordinate, HeadingMode, LitchiMission, MissionConfig, Waypoint},
+    };
+    use serde_json::json;
+
+    use crate::{
+        error::{LitchiApiError, ParseError},
+        transport::{HttpRequest, HttpResponse, HttpTransport},
+        ClientOptions, Device, LitchiApi, LitchiApiConfig, Mission, MissionFile, ObjectId,
+        RetryPolicy, SessionData, UploadOptions,
+    };
+
+    const MISSION_DATA: &[u8] = include_bytes!("../../litchitool/test/litchi_mission.csv");
+
+    async fn mock_login(server: &MockServer) -> LitchiApi {
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/parse/login"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "objectId": "user1",
+                "username": "pilot",
+                "email": "pilot@example.com",
+                "name": "Pilot",
+                "emailVerified": true,
+                "sessionToken": "r:token",
+            })))
+            .mount(server)
+            .await;
+
+        LitchiApi::login_with_config(
+            &LitchiApiConfig {
+                base_url: server.uri(),
+                allow_insecure: true,
+                ..Default::default()
+            },
+            "pilot",
+            "password",
+        )
+        .await
+        .expect("Mock login should succeed")
+    }
+
+    fn test_mission() -> LitchiMission {
+        litchitool::csv_format::read_from_csv(csv::Reader::from_reader(MISSION_DATA))
+            .expect("Could not parse csv mission")
+    }
 
     #[tokio::test]
-    async fn test_upload_mission() -> Result<(), LitchiApiError> {
-        const MISSION_DATA: &[u8] = include_bytes!("../../litchitool/test/litchi_mission.csv");
+    async fn test_upload_metadata() {
+        let server = MockServer::start().await;
+        let api = mock_login(&server).await;
+
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/parse/files/mission"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "name": "file.mission",
+                "url": "https://files.example.com/file.mission",
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/parse/classes/Mission"))
+            .and(matchers::body_partial_json(json!({
+                "name": "survey",
+                "project": "P-42",
+                "pilot": "Pilot",
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"objectId": "mission1"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let options = UploadOptions {
+            metadata: json!({ "project": "P-42", "pilot": "Pilot", "name": "overridden" })
+                .as_object()
+                .unwrap()
+                .clone(),
+        };
+        api.upload_with_options(&test_mission(), "survey", &options)
+            .await
+            .expect("Upload should succeed");
+
+        let listed = Mission::try_from(&json!({
+            "objectId": "mission1",
+            "location": { "__type": "GeoPoint", "latitude": 1.0, "longitude": 2.0 },
+            "name": "survey",
+            "user": { "__type": "Pointer", "className": "_User", "objectId": "user1" },
+            "file": { "__type": "File", "name": "file.mission", "url": "https://files.example.com/file.mission" },
+            "createdAt": "2023-08-01T00:00:00.000Z",
+            "project": "P-42",
+            "pilot": "Pilot",
+        }))
+        .unwrap();
+        assert_eq!(listed.metadata.len(), 2);
+        assert_eq!(listed.metadata["project"], "P-42");
+    }
 
+    #[tokio::test]
+    async fn test_missions_cached() {
+        let server = MockServer::start().await;
+        let api = mock_login(&server).await;
+
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/parse/classes/Mission"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "results": [] })))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::method("DELETE"))
+            .and(matchers::path("/parse/classes/Mission/mission1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .mount(&server)
+            .await;
+
+        let ttl = Duration::from_secs(60);
+        api.missions_cached(ttl).await.unwrap();
+        api.missions_cached(ttl).await.unwrap();
+
+        // Deleting invalidates the cache, so this is the second request
+        api.delete_mission(ObjectId("mission1".to_string()))
+            .await
+            .unwrap();
+        api.missions_cached(ttl).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_missions_null_island() {
+        let server = MockServer::start().await;
+        let api = mock_login(&server).await;
+
+        let mission = |object_id: &str, latitude: f64, longitude: f64| {
+            json!({
+                "objectId": object_id,
+                "location": { "__type": "GeoPoint", "latitude": latitude, "longitude": longitude },
+                "name": object_id,
+                "user": { "__type": "Pointer", "className": "_User", "objectId": "user1" },
+                "file": { "__type": "File", "name": "file.mission", "url": "https://files.example.com/file.mission" },
+            })
+        };
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/parse/classes/Mission"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "results": [mission("empty", 0., 0.), mission("survey", 1., 2.)],
+            })))
+            .mount(&server)
+            .await;
+
+        let missions = api.missions().await.expect("Listing should succeed");
+        let null_island: Vec<_> = missions
+            .iter()
+            .map(|mission| (mission.name.as_str(), mission.is_null_island()))
+            .collect();
+        assert_eq!(null_island, [("empty", true), ("survey", false)]);
+    }
+
+    #[tokio::test]
+    async fn test_download_mission_bytes() {
+        let server = MockServer::start().await;
+        let api = mock_login(&server).await;
+
+        let file = test_mission().to_binary();
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/files/survey.mission"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(file.to_vec()))
+            .mount(&server)
+            .await;
+
+        let mission = Mission {
+            object_id: ObjectId("survey".to_string()),
+            location: Coordinate(1., 2.),
+            name: "Survey".to_string(),
+            user_id: ObjectId("user1".to_string()),
+            file: MissionFile {
+                name: "survey.mission".to_string(),
+                url: format!("{}/files/survey.mission", server.uri()),
+            },
+            metadata: Default::default(),
+        };
+        let bytes = api
+            .download_mission_bytes(&mission)
+            .await
+            .expect("Download should succeed");
+        assert_eq!(bytes, file);
+
+        let missing = Mission {
+            file: MissionFile {
+                name: "missing.mission".to_string(),
+                url: format!("{}/files/missing.mission", server.uri()),
+            },
+            ..mission
+        };
+        assert!(api.download_mission_bytes(&missing).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_from_session() {
+        let server = MockServer::start().await;
+        let api = mock_login(&server).await;
+        let stored = serde_json::to_string(api.user_data()).unwrap();
+        let config = LitchiApiConfig {
+            base_url: server.uri(),
+            allow_insecure: true,
+            ..Default::default()
+        };
+
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/parse/users/me"))
+            .and(matchers::header("X-Parse-Session-Token", "r:token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "objectId": "user1" })))
+            .mount(&server)
+            .await;
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/parse/users/me"))
+            .respond_with(
+                ResponseTemplate::new(400)
+                    .set_body_json(json!({ "code": 209, "error": "Invalid session token" })),
+            )
+            .mount(&server)
+            .await;
+
+        let session: SessionData = serde_json::from_str(&stored).unwrap();
+        let resumed = LitchiApi::from_session_with_config(&config, session)
+            .await
+            .expect("Stored session should be valid");
+        assert_eq!(resumed.user_data().object_id, "user1");
+
+        let mut expired: SessionData = serde_json::from_str(&stored).unwrap();
+        expired.session_token = "r:expired".to_string();
+        assert!(matches!(
+            LitchiApi::from_session_with_config(&config, expired).await,
+            Err(LitchiApiError::AuthError(ParseError {
+                parse_code: Some(ParseError::INVALID_SESSION_TOKEN),
+                ..
+            }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rename_mission_request() {
+        let server = MockServer::start().await;
+        let api = mock_login(&server).await.with_retry_policy(RetryPolicy {
+            max_attempts: 2,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+        });
+
+        // Renaming is idempotent, so it is retried after server errors
+        Mock::given(matchers::method("PUT"))
+            .and(matchers::path("/parse/classes/Mission/mission1"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("Internal Server Error"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::method("PUT"))
+            .and(matchers::path("/parse/classes/Mission/mission1"))
+            .and(matchers::header("X-Parse-Session-Token", "r:token"))
+            .and(matchers::body_json(json!({ "name": "Survey north" })))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "updatedAt": "2023-08-01" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        api.rename_mission(&ObjectId("mission1".to_string()), "Survey north")
+            .await
+            .expect("Rename should succeed");
+    }
+
+    #[tokio::test]
+    async fn test_update_mission_request() {
+        let server = MockServer::start().await;
+        let api = mock_login(&server).await;
+        let mission = test_mission();
+
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/parse/files/mission"))
+            .and(matchers::body_bytes(mission.to_binary().to_vec()))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "name": "updated.mission",
+                "url": "https://files.example.com/updated.mission",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let first = &mission.waypoints()[0].coordinate;
+        Mock::given(matchers::method("PUT"))
+            .and(matchers::path("/parse/classes/Mission/mission1"))
+            .and(matchers::header("X-Parse-Session-Token", "r:token"))
+            .and(matchers::body_json(json!({
+                "location": { "__type": "GeoPoint", "latitude": first.0, "longitude": first.1 },
+                "name": "Survey north",
+                "file": {
+                    "__type": "File",
+                    "name": "updated.mission",
+                    "url": "https://files.example.com/updated.mission",
+                },
+            })))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "updatedAt": "2023-08-01" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        api.update_mission(
+            &ObjectId("mission1".to_string()),
+            &mission,
+            Some("Survey north"),
+        )
+        .await
+        .expect("Update should succeed");
+    }
+
+    #[tokio::test]
+    async fn test_devices() {
+        let server = MockServer::start().await;
+        let api = mock_login(&server).await;
+
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/parse/classes/Device"))
+            .and(matchers::header("X-Parse-Session-Token", "r:token"))
+            .and(matchers::body_partial_json(json!({
+                "where": { "user": { "objectId": "user1" } },
+            })))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "results": [
+                {
+                    "objectId": "device1",
+                    "name": "Mavic 3",
+                    "lastSync": { "__type": "Date", "iso": "2023-08-01T12:00:00.000Z" },
+                },
+                { "objectId": "device2", "name": "Mini 3 Pro" },
+            ] })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let devices: Vec<Device> = api.devices().await.expect("Devices should be listed");
+
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].object_id.0, "device1");
+        assert_eq!(devices[0].name, "Mavic 3");
+        assert_eq!(
+            devices[0].last_sync.as_deref(),
+            Some("2023-08-01T12:00:00.000Z")
+        );
+        assert_eq!(devices[1].last_sync, None);
+    }
+
+    #[tokio::test]
+    async fn test_upload_unique() {
+        let server = MockServer::start().await;
+        let api = mock_login(&server).await;
+
+        let mission = |object_id: &str| {
+            json!({
+                "objectId": object_id,
+                "location": { "latitude": 48.0, "longitude": 11.0 },
+                "name": "survey",
+                "user": { "objectId": "user1" },
+                "file": { "name": "file.mission", "url": "https://files.example.com/file.mission" },
+            })
+        };
+        // Listed with the new mission, which the overwriting upload must keep
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/parse/classes/Mission"))
+            .and(matchers::body_partial_json(
+                json!({ "where": { "name": "survey" } }),
+            ))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(
+                    json!({ "results": [mission("mission1"), mission("mission2")] }),
+                ),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(matchers::method("DELETE"))
+            .and(matchers::path("/parse/classes/Mission/mission1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::method("DELETE"))
+            .and(matchers::path("/parse/classes/Mission/mission2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(0)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/parse/files/mission"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "name": "new.mission",
+                "url": "https://files.example.com/new.mission",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/parse/classes/Mission"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"objectId": "mission2"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let result = api.upload_unique(&test_mission(), "survey").await;
+        assert!(
+            matches!(result, Err(LitchiApiError::DuplicateMissionName(ref name)) if name == "survey"),
+            "Duplicate name should be refused, got {result:?}"
+        );
+
+        let uploaded = api
+            .upload_overwriting(&test_mission(), "survey")
+            .await
+            .expect("Overwriting upload should succeed");
+        assert_eq!(uploaded.0, "mission2");
+
+        // The old mission is only deleted once the new one exists
+        let requests = server.received_requests().await.unwrap();
+        let position = |method: &str, path: &str| {
+            requests
+                .iter()
+                .position(|request| request.method.as_str() == method && request.url.path() == path)
+                .unwrap()
+        };
+        assert!(
+            position("POST", "/parse/classes/Mission")
+                < position("DELETE", "/parse/classes/Mission/mission1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_logout() {
+        let server = MockServer::start().await;
+        let api = mock_login(&server).await;
+
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/parse/logout"))
+            .and(matchers::header("X-Parse-Session-Token", "r:token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        api.logout().await.expect("Logout should succeed");
+    }
+
+    #[tokio::test]
+    async fn test_download_mission() {
+        let server = MockServer::start().await;
+        let api = mock_login(&server).await;
+
+        let uploaded = test_mission();
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/files/survey.mission"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(uploaded.to_binary().to_vec()))
+            .mount(&server)
+            .await;
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/files/broken.mission"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"not a mission".to_vec()))
+            .mount(&server)
+            .await;
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/files/missing.mission"))
+            .respond_with(ResponseTemplate::new(404).set_body_string("Not Found"))
+            .mount(&server)
+            .await;
+
+        let mission = |file: &str| Mission {
+            object_id: ObjectId(file.to_string()),
+            location: Coordinate(1., 2.),
+            name: file.to_string(),
+            user_id: ObjectId("user1".to_string()),
+            file: MissionFile {
+                name: format!("{file}.mission"),
+                url: format!("{}/files/{file}.mission", server.uri()),
+            },
+            metadata: Default::default(),
+        };
+
+        let downloaded = api
+            .download_mission(&mission("survey"))
+            .await
+            .expect("Download should succeed");
+        assert_eq!(downloaded, uploaded);
+        assert!(matches!(
+            api.download_mission(&mission("broken")).await,
+            Err(LitchiApiError::MissionFormatError(_))
+        ));
+        assert!(matches!(
+            api.download_mission(&mission("missing")).await,
+            Err(LitchiApiError::ParseError(ParseError {
+                http_status: 404,
+                ..
+            }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_missions_paged() {
+        let server = MockServer::start().await;
+        let api = mock_login(&server).await;
+
+        let mission = |index: usize| {
+            json!({
+                "objectId": format!("mission{index}"),
+                "location": { "__type": "GeoPoint", "latitude": 1., "longitude": 2. },
+                "name": format!("mission{index}"),
+                "user": { "__type": "Pointer", "className": "_User", "objectId": "user1" },
+                "file": { "__type": "File", "name": "file.mission", "url": "https://files.example.com/file.mission" },
+            })
+        };
+        let page = |range: std::ops::Range<usize>| {
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "results": range.map(mission).collect::<Vec<_>>() }))
+        };
+        let user_filter = json!({
+            "where": { "user": { "__type": "Pointer", "className": "_User", "objectId": "user1" } }
+        });
+        for (skip, range) in [(0, 0..100), (100, 100..150)] {
+            Mock::given(matchers::method("GET"))
+                .and(matchers::path("/parse/classes/Mission"))
+                .and(matchers::query_param("limit", "100"))
+                .and(matchers::query_param("skip", skip.to_string()))
+                .and(matchers::query_param("order", "createdAt,objectId"))
+                .and(matchers::body_json(&user_filter))
+                .respond_with(page(range))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/parse/classes/Mission"))
+            .and(matchers::query_param("skip", "300"))
+            .respond_with(page(0..0))
+            .mount(&server)
+            .await;
+
+        let missions = api.missions().await.expect("Listing should succeed");
+        assert_eq!(missions.len(), 150);
+        assert_eq!(missions[149].name, "mission149");
+
+        let empty = api
+            .missions_paged(10, 300)
+            .await
+            .expect("Empty page should succeed");
+        assert!(empty.is_empty());
+    }
+
+    /// Answers requests with canned responses in order, recording the requests
+    #[derive(Default)]
+    struct MockTransport {
+        responses: Mutex<VecDeque<serde_json::Value>>,
+        requests: Mutex<Vec<HttpRequest>>,
+    }
+
+    impl HttpTransport for MockTransport {
+        async fn execute(&self, request: HttpRequest) -> Result<HttpResponse, LitchiApiError> {
+            self.requests.lock().unwrap().push(request);
+            let response = self
+                .responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .expect("Unexpected request");
+
+            Ok(HttpResponse {
+                status: 200,
+                body: serde_json::to_vec(&response)?,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upload_payload_with_mock_transport() {
+        let transport = MockTransport {
+            responses: Mutex::new(VecDeque::from([
+                json!({
+                    "objectId": "user1",
+                    "username": "pilot",
+                    "email": "pilot@example.com",
+                    "name": "Pilot",
+                    "emailVerified": true,
+                    "sessionToken": "r:token",
+                }),
+                json!({ "name": "file.mission", "url": "https://files.example.com/file.mission" }),
+                json!({ "objectId": "mission1" }),
+            ])),
+            ..Default::default()
+        };
+        let api = LitchiApi::login_with_transport(transport, "https://parse.test", "pilot", "pw")
+            .await
+            .expect("Mock login should succeed");
+
+        let mission = test_mission();
+        let uploaded = api
+            .upload(&mission, "survey")
+            .await
+            .expect("Upload should succeed");
+        assert_eq!(uploaded.0, "mission1");
+
+        let requests = api.transport.requests.lock().unwrap();
+        let file_upload = &requests[1];
+        assert_eq!(file_upload.url, "https://parse.test/parse/files/mission");
+        assert_eq!(file_upload.body.as_deref(), Some(&mission.to_binary()[..]));
+
+        let mission_object = &requests[2];
+        assert_eq!(
+            mission_object.url,
+            "https://parse.test/parse/classes/Mission"
+        );
+        assert!(mission_object
+            .headers
+            .contains(&("X-Parse-Session-Token".to_string(), "r:token".to_string())));
+        let first = &mission.waypoints()[0].coordinate;
+        assert_eq!(
+            mission_object.json_body(),
+            Some(json!({
+                "ACL": { "user1": { "read": true, "write": true } },
+                "location": { "__type": "GeoPoint", "latitude": first.0, "longitude": first.1 },
+                "name": "survey",
+                "user": { "__type": "Pointer", "className": "_User", "objectId": "user1" },
+                "file": {
+                    "__type": "File",
+                    "name": "file.mission",
+                    "url": "https://files.example.com/file.mission",
+                },
+            }))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upload_receipt() {
+        let transport = MockTransport {
+            responses: Mutex::new(VecDeque::from([
+                json!({
+                    "objectId": "user1",
+                    "username": "pilot",
+                    "email": "pilot@example.com",
+                    "name": "Pilot",
+                    "emailVerified": true,
+                    "sessionToken": "r:token",
+                }),
+                json!({ "name": "file.mission", "url": "https://files.example.com/file.mission" }),
+                json!({ "objectId": "mission1" }),
+            ])),
+            ..Default::default()
+        };
+        let api = LitchiApi::login_with_transport(transport, "https://parse.test", "pilot", "pw")
+            .await
+            .expect("Mock login should succeed");
+
+        let mission = test_mission();
+        let receipt = api
+            .upload_with_receipt(&mission, "survey", &UploadOptions::default())
+            .await
+            .expect("Upload should succeed");
+
+        assert_eq!(receipt.object_id.0, "mission1");
+        assert_eq!(receipt.bytes_uploaded, mission.to_binary().len());
+        assert_eq!(receipt.file_url, "https://files.example.com/file.mission");
+    }
+
+    #[test]
+    fn test_insecure_base_url() {
+        let mut config = LitchiApiConfig {
+            base_url: "http://parse.litchiapi.com".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            super::build_client(&config.clone().into()),
+            Err(LitchiApiError::InsecureBaseUrl(_))
+        ));
+
+        config.allow_insecure = true;
+        super::build_client(&config.clone().into())
+            .expect("Insecure URL should be allowed explicitly");
+    }
+
+    #[tokio::test]
+    async fn test_cross_origin_redirect() {
+        let server = MockServer::start().await;
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/parse/health"))
+            .respond_with(
+                ResponseTemplate::new(302).insert_header("Location", "http://example.com/health"),
+            )
+            .mount(&server)
+            .await;
+
+        let result = LitchiApi::ping(&LitchiApiConfig {
+            base_url: server.uri(),
+            allow_insecure: true,
+            ..Default::default()
+        })
+        .await;
+
+        assert!(
+            matches!(result, Err(LitchiApiError::ReqwestError(ref error)) if error.is_redirect()),
+            "Redirect should be refused, got {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_client_with_proxy() {
+        for proxy in ["http://proxy.example.com:3128", "socks5://127.0.0.1:1080"] {
+            let config = LitchiApiConfig {
+                proxy: Some(reqwest::Proxy::all(proxy).unwrap()),
+                ..Default::default()
+            };
+
+            super::build_client(&config.clone().into()).expect("Client should accept the proxy");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ping_healthy() {
+        let server = MockServer::start().await;
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/parse/health"))
+            .and(matchers::header("x-parse-application-id", super::APP_ID))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"status":"ok"}"#))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        LitchiApi::ping(&LitchiApiConfig {
+            base_url: server.uri(),
+            allow_insecure: true,
+            ..Default::default()
+        })
+        .await
+        .expect("Healthy backend should answer the ping");
+    }
+
+    #[tokio::test]
+    async fn test_ping_unreachable() {
+        // Grab a free port and close it again, so nothing is listening there
+        let address = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .unwrap();
+
+        let result = LitchiApi::ping(&LitchiApiConfig {
+            base_url: format!("http://{address}"),
+            allow_insecure: true,
+            ..Default::default()
+        })
+        .await;
+
+        assert!(matches!(result, Err(LitchiApiError::ReqwestError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout() {
+        let server = MockServer::start().await;
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/parse/users/me"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+            .mount(&server)
+            .await;
+
+        let session_data: SessionData = serde_json::from_value(json!({
+            "objectId": "user1",
+            "username": "pilot",
+            "email": "pilot@example.com",
+            "name": "Pilot",
+            "emailVerified": true,
+            "sessionToken": "token",
+        }))
+        .unwrap();
+        let result = LitchiApi::from_session_with_options(
+            session_data,
+            ClientOptions {
+                config: LitchiApiConfig {
+                    base_url: server.uri(),
+                    allow_insecure: true,
+                    ..Default::default()
+                },
+                timeout: Duration::from_millis(100),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        assert!(
+            matches!(result, Err(LitchiApiError::Timeout(_))),
+            "Slow response should time out, got {:?}",
+            result.err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upload_invalid_mission() {
+        let server = MockServer::start().await;
+        let api = mock_login(&server).await;
+
+        let mut mission =
+            LitchiMission::new(vec![Waypoint::default()], vec![], MissionConfig::default())
+                .unwrap();
+        // Initial headings must be within -180..=180
+        mission.config_mut().heading_mode = HeadingMode::Initial;
+        mission.config_mut().initial_heading = Some(270.);
+
+        let result = api.upload(&mission, "invalid").await;
+
+        assert!(matches!(
+            result,
+            Err(LitchiApiError::Mission(LitchiError::InvalidMission(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_upload_resumes_after_interruption() {
+        let server = MockServer::start().await;
+        let api = mock_login(&server).await;
+
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/parse/files/mission"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "name": "file.mission",
+                "url": "https://files.example.com/file.mission",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/parse/classes/Mission"))
+            .respond_with(ResponseTemplate::new(503).set_body_string("Service Unavailable"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/parse/classes/Mission"))
+            .and(matchers::body_partial_json(json!({
+                "file": { "name": "file.mission" },
+            })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({"objectId": "mission1"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        // The failed attempt didn't create the object
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/parse/classes/Mission"))
+            .and(matchers::body_partial_json(json!({
+                "where": { "file": { "name": "file.mission" } },
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "results": [] })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let uploaded = api
+            .upload(&test_mission(), "interrupted")
+            .await
+            .expect("Upload should recover from the interruption");
+
+        assert_eq!(uploaded.0, "mission1");
+    }
+
+    #[tokio::test]
+    async fn test_upload_finds_created_object() {
+        let server = MockServer::start().await;
+        let api = mock_login(&server).await;
+
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/parse/files/mission"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+                "name": "file.mission",
+                "url": "https://files.example.com/file.mission",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        // The proxy fails, but the object was created behind it
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/parse/classes/Mission"))
+            .respond_with(ResponseTemplate::new(502).set_body_string("Bad Gateway"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/parse/classes/Mission"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "results": [{ "objectId": "mission1" }] })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let uploaded = api
+            .upload(&test_mission(), "interrupted")
+            .await
+            .expect("Upload should find the created object");
+
+        assert_eq!(uploaded.0, "mission1");
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_after_post_timeout() {
+        let server = MockServer::start().await;
+        mock_login(&server).await;
+        let api = LitchiApi::login_with_options(
+            "pilot",
+            "password",
+            ClientOptions {
+                config: LitchiApiConfig {
+                    base_url: server.uri(),
+                    allow_insecure: true,
+                    ..Default::default()
+                },
+                timeout: Duration::from_millis(200),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        // The server may store the file although the client gave up waiting
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/parse/files/mission"))
+            .respond_with(ResponseTemplate::new(201).set_delay(Duration::from_secs(5)))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/parse/classes/Mission"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("Internal Server Error"))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let result = api.upload(&test_mission(), "slow").await;
+        assert!(
+            matches!(result, Err(LitchiApiError::Timeout(_))),
+            "Timed out upload should not be resent, got {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(300),
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(300));
+        assert_eq!(policy.backoff(40), Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn test_retry_server_errors() {
+        let server = MockServer::start().await;
+        let api = mock_login(&server).await.with_retry_policy(RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+        });
+
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/parse/classes/Mission"))
+            .respond_with(ResponseTemplate::new(502).set_body_string("Bad Gateway"))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::method("GET"))
+            .and(matchers::path("/parse/classes/Mission"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "results": [] })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(matchers::method("POST"))
+            .and(matchers::path("/parse/functions/syncMyDevices"))
+            .respond_with(ResponseTemplate::new(503).set_body_string("Service Unavailable"))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        assert!(api.missions().await.unwrap().is_empty());
+        assert!(matches!(
+            api.sync_devices().await,
+            Err(LitchiApiError::ParseError(ParseError {
+                http_status: 503,
+                ..
+            }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_client_errors() {
+        let server = MockServer::start().await;
+        let api = mock_login(&server).await;
+
+        Mock::given(matchers::method("DELETE"))
+            .and(matchers::path("/parse/classes/Mission/mission1"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+                "code": 209,
+                "error": "invalid session token",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        assert!(matches!(
+            api.delete_mission(ObjectId("mission1".to_string())).await,
+            Err(LitchiApiError::ParseError(ParseError {
+                http_status: 401,
+                ..
+            }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_upload_mission() -> Result<(), LitchiApiError> {
         let secret_username =
             std::env::var("LITCHI_USERNAME").expect("Must have $LITCHI_USERNAME set for tests");
         let secret_password =
             std::env::var("LITCHI_PASSWORD").expect("Must have $LITCHI_PASSWORD set for tests");
 
-        let mission = litchitool::csv_format::read_from_csv(csv::Reader::from_reader(MISSION_DATA))
-            .expect("Could not parse csv mission");
+        let mission = test_mission();
 
         let api = LitchiApi::login(&secret_username, &secret_password).await?;
 
@@ -269,4 +2033,91 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_rename_mission() -> Result<(), LitchiApiError> {
+        let secret_username =
+            std::env::var("LITCHI_USERNAME").expect("Must have $LITCHI_USERNAME set for tests");
+        let secret_password =
+            std::env::var("LITCHI_PASSWORD").expect("Must have $LITCHI_PASSWORD set for tests");
+
+        let api = LitchiApi::login(&secret_username, &secret_password).await?;
+        let uploaded = api.upload(&test_mission(), "testingrename").await?;
+
+        api.rename_mission(&uploaded, "testingrenamed").await?;
+
+        let current_missions = api.missions().await?;
+        assert!(current_missions
+            .iter()
+            .any(|mission| mission.object_id == uploaded && mission.name == "testingrenamed"));
+
+        api.delete_mission(uploaded).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_mission() -> Result<(), LitchiApiError> {
+        let secret_username =
+            std::env::var("LITCHI_USERNAME").expect("Must have $LITCHI_USERNAME set for tests");
+        let secret_password =
+            std::env::var("LITCHI_PASSWORD").expect("Must have $LITCHI_PASSWORD set for tests");
+
+        let api = LitchiApi::login(&secret_username, &secret_password).await?;
+        let mission = test_mission();
+        let uploaded = api.upload(&mission, "testingupdate").await?;
+
+        let mut updated = mission.clone();
+        updated.append(&mission)?;
+        api.update_mission(&uploaded, &updated, None).await?;
+
+        let stored = api
+            .missions()
+            .await?
+            .into_iter()
+            .find(|mission| mission.object_id == uploaded)
+            .expect("Updated mission should keep its object id");
+        let downloaded = api.download_mission(&stored).await?;
+        assert_eq!(downloaded.waypoints().len(), 2 * mission.waypoints().len());
+
+        api.delete_mission(uploaded).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_upload_unique_mission() -> Result<(), LitchiApiError> {
+        let secret_username =
+            std::env::var("LITCHI_USERNAME").expect("Must have $LITCHI_USERNAME set for tests");
+        let secret_password =
+            std::env::var("LITCHI_PASSWORD").expect("Must have $LITCHI_PASSWORD set for tests");
+
+        let api = LitchiApi::login(&secret_username, &secret_password).await?;
+        let uploaded = api.upload_unique(&test_mission(), "testingunique").await?;
+
+        let duplicate = api.upload_unique(&test_mission(), "testingunique").await;
+        assert!(matches!(
+            duplicate,
+            Err(LitchiApiError::DuplicateMissionName(_))
+        ));
+
+        let replaced = api
+            .upload_overwriting(&test_mission(), "testingunique")
+            .await?;
+        let current_missions = api.missions().await?;
+        assert!(!current_missions
+            .iter()
+            .any(|mission| mission.object_id == uploaded));
+        assert_eq!(
+            current_missions
+                .iter()
+                .filter(|mission| mission.name == "testingunique")
+                .count(),
+            1
+        );
+
+        api.delete_mission(replaced).await?;
+
+        Ok(())
+    }
 }
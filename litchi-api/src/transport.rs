@@ -0,0 +1,115 @@
+use std::future::Future;
+
+use reqwest::{Client, Method};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::error::LitchiApiError;
+
+/// A request to the Parse backend, independent of the HTTP client sending it
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+/// Sends requests to the Parse backend. [`ReqwestTransport`] is used by default, other
+/// implementations allow testing without a server.
+pub trait HttpTransport {
+    fn execute(
+        &self,
+        request: HttpRequest,
+    ) -> impl Future<Output = Result<HttpResponse, LitchiApiError>> + Send;
+}
+
+/// Sends requests with a [`reqwest::Client`], which keeps the session cookies
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport(pub Client);
+
+impl HttpRequest {
+    pub fn new(method: Method, url: impl Into<String>) -> Self {
+        Self {
+            method,
+            url: url.into(),
+            headers: vec![],
+            body: None,
+        }
+    }
+
+    pub fn get(url: impl Into<String>) -> Self {
+        Self::new(Method::GET, url)
+    }
+
+    pub fn post(url: impl Into<String>) -> Self {
+        Self::new(Method::POST, url)
+    }
+
+    pub fn put(url: impl Into<String>) -> Self {
+        Self::new(Method::PUT, url)
+    }
+
+    pub fn delete(url: impl Into<String>) -> Self {
+        Self::new(Method::DELETE, url)
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Sets a JSON body and the matching content type
+    pub fn json<T: Serialize + ?Sized>(self, body: &T) -> Result<Self, LitchiApiError> {
+        Ok(self
+            .header("Content-Type", "application/json")
+            .body(serde_json::to_vec(body)?))
+    }
+
+    /// The JSON body of the request, `None` if it has no body or it isn't JSON
+    pub fn json_body(&self) -> Option<serde_json::Value> {
+        serde_json::from_slice(self.body.as_ref()?).ok()
+    }
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, LitchiApiError> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+}
+
+impl HttpTransport for ReqwestTransport {
+    async fn execute(&self, request: HttpRequest) -> Result<HttpResponse, LitchiApiError> {
+        let mut builder = self.0.request(request.method, request.url);
+        for (name, value) in request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+
+        let response = builder.send().await?;
+        Ok(HttpResponse {
+            status: response.status().as_u16(),
+            body: response.bytes().await?.to_vec(),
+        })
+    }
+}
@@ -1,13 +1,28 @@
+use std::time::Duration;
+
 use litchitool::mission::Coordinate;
-use serde::Deserialize;
-use serde_json::Value;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 use crate::error::LitchiApiError;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ObjectId(pub(crate) String);
 
-#[derive(Debug, Deserialize)]
+impl ObjectId {
+    /// Id of an existing Parse object, like one printed by the CLI
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// User and session token of a login. It can be stored to resume the session later with
+/// [`crate::LitchiApi::from_session`], so keep it as secret as the password.
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SessionData {
     #[serde(rename = "objectId")]
     pub object_id: String,
@@ -30,11 +45,63 @@ pub struct MissionFile {
 pub struct Mission {
     pub object_id: ObjectId,
     pub location: Coordinate,
+    /// Name of the mission, which is not part of the binary mission file
     pub name: String,
     pub user_id: ObjectId,
     pub file: MissionFile,
+    /// Custom fields stored alongside the mission, see [`UploadOptions::metadata`]
+    pub metadata: Map<String, Value>,
 }
 
+impl Mission {
+    /// Whether the mission is located at (0, 0), which usually means its location was never
+    /// computed when it was uploaded
+    pub fn is_null_island(&self) -> bool {
+        self.location.0 == 0. && self.location.1 == 0.
+    }
+}
+
+/// An aircraft or phone of the user that receives missions when devices are synced, see
+/// [`crate::LitchiApi::devices`]
+#[derive(Debug, Clone)]
+pub struct Device {
+    pub object_id: ObjectId,
+    pub name: String,
+    /// When the device last synced as an ISO 8601 timestamp, `None` if it never did
+    pub last_sync: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UploadOptions {
+    /// Extra fields merged into the Parse mission object. Fields used by Litchi itself (like
+    /// `name` or `file`) can't be overridden.
+    pub metadata: Map<String, Value>,
+}
+
+/// Details of a finished upload, see [`crate::LitchiApi::upload_with_receipt`]
+#[derive(Debug, Clone)]
+pub struct UploadReceipt {
+    pub object_id: ObjectId,
+    /// Size of the uploaded mission binary
+    pub bytes_uploaded: usize,
+    /// Wall-clock time of the whole upload, including retries
+    pub duration: Duration,
+    /// URL the mission file is stored at
+    pub file_url: String,
+}
+
+/// Fields of the Parse mission object that are not custom metadata
+pub(crate) const MISSION_FIELDS: [&str; 8] = [
+    "objectId",
+    "location",
+    "name",
+    "user",
+    "file",
+    "ACL",
+    "createdAt",
+    "updatedAt",
+];
+
 impl TryFrom<&Value> for Mission {
     type Error = LitchiApiError;
 
@@ -78,6 +145,13 @@ impl TryFrom<&Value> for Mission {
                 })
             })
             .ok_or(format_error("Could not get file of mission"))?;
+        let metadata = value
+            .as_object()
+            .ok_or(format_error("Mission is not an object"))?
+            .iter()
+            .filter(|(key, _)| !MISSION_FIELDS.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
 
         Ok(Mission {
             object_id,
@@ -85,6 +159,41 @@ impl TryFrom<&Value> for Mission {
             name,
             user_id,
             file,
+            metadata,
+        })
+    }
+}
+
+impl TryFrom<&Value> for Device {
+    type Error = LitchiApiError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let format_error = |error: &str| {
+            LitchiApiError::ResponseFormateError(error.to_string(), value.to_string())
+        };
+
+        let object_id = ObjectId(
+            value
+                .get("objectId")
+                .and_then(|value| value.as_str())
+                .ok_or(format_error("Could not get objectId of device"))?
+                .to_string(),
+        );
+        let name = value
+            .get("name")
+            .and_then(|name| name.as_str())
+            .ok_or(format_error("Could not get name of device"))?
+            .to_string();
+        // Parse stores dates as `{"__type": "Date", "iso": "..."}`
+        let last_sync = value
+            .get("lastSync")
+            .and_then(|date| date.get("iso").unwrap_or(date).as_str())
+            .map(str::to_string);
+
+        Ok(Device {
+            object_id,
+            name,
+            last_sync,
         })
     }
 }
@@ -1,12 +1,17 @@
 use std::path::{Path, PathBuf};
 
-use clap::{Parser, Subcommand};
-use litchi_api::LitchiApi;
+use anyhow::{bail, Context};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use litchi_api::{LitchiApi, LitchiApiConfig, Mission, ObjectId};
 use litchitool::{
-    csv_format::{csv, read_from_csv},
-    mission::LitchiMission,
+    binary_format, convert,
+    csv_format::{csv, read_from_csv, read_from_csv_collecting, write_to_csv},
+    diff::Difference,
+    error::LitchiError,
+    mission::{AltitudeMode, Coordinate, GeofenceMode, LitchiMission, PhotoInterval},
 };
 use serde::Deserialize;
+use tracing::{error, info, level_filters::LevelFilter};
 use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
 
 #[derive(Parser)]
@@ -18,10 +23,65 @@ pub struct Cli {
     pretty_logs: bool,
 }
 
-#[derive(Deserialize)]
-pub struct UploadConfig {
-    username: String,
-    password: String,
+/// Settings file shared by a team. Everything is optional; credentials missing from it are taken
+/// from `$LITCHI_USERNAME` and `$LITCHI_PASSWORD`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CliConfig {
+    username: Option<String>,
+    password: Option<String>,
+    /// Base URL of the Parse server, see [`LitchiApiConfig::base_url`]
+    base_url: Option<String>,
+    /// Applied to converted and uploaded missions, unless overridden by flags
+    #[serde(flatten)]
+    mission: MissionSettings,
+    /// Waypoints outside of it are removed or clamped onto it
+    geofence: Option<GeofenceConfig>,
+}
+
+/// Mission settings given in the [`CliConfig`] or as flags
+#[derive(Debug, Clone, Default, Deserialize, Args)]
+#[serde(default)]
+pub struct MissionSettings {
+    /// Cruising speed in m/s
+    #[arg(long)]
+    cruising_speed: Option<f32>,
+    /// Maximum speed in m/s when flown with the remote controller
+    #[arg(long)]
+    rc_speed: Option<f32>,
+    /// Reference of all waypoint altitudes
+    #[arg(long, value_enum)]
+    altitude_mode: Option<AltitudeReference>,
+    /// Seconds between photos for the whole mission
+    #[arg(long, conflicts_with = "photo_distance_interval")]
+    photo_time_interval: Option<f32>,
+    /// Meters between photos for the whole mission
+    #[arg(long)]
+    photo_distance_interval: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum AltitudeReference {
+    AboveTakeoff,
+    AboveGround,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GeofenceConfig {
+    /// Latitude and longitude of the south west corner
+    south_west: (f64, f64),
+    /// Latitude and longitude of the north east corner
+    north_east: (f64, f64),
+    /// Move waypoints outside onto the geofence instead of removing them
+    #[serde(default)]
+    clamp: bool,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum MissionFormat {
+    Binary,
+    Csv,
 }
 
 #[derive(Subcommand)]
@@ -32,15 +92,79 @@ pub enum Command {
         input: PathBuf,
         /// Output file path
         output: PathBuf,
+        /// Remove all POIs from the mission
+        #[arg(long)]
+        strip_pois: bool,
+        /// Settings file applied to the mission
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        #[command(flatten)]
+        settings: MissionSettings,
+    },
+    /// Convert every CSV file in a directory to a litchi mission file
+    ConvertDir {
+        /// Directory containing the CSV files
+        input: PathBuf,
+        /// Directory the mission files are written to, named like the CSV files
+        output: PathBuf,
     },
     /// Upload a CSV file to the litch cloud
     Upload {
         /// File to upload
         input: PathBuf,
+        /// Settings file with the credentials and settings applied to the mission
         #[arg(short, long)]
-        config: PathBuf,
+        config: Option<PathBuf>,
         #[arg(short, long)]
         name: String,
+        #[command(flatten)]
+        settings: MissionSettings,
+    },
+    /// Download a mission from the litchi cloud
+    Download {
+        /// Object id of the mission
+        id: String,
+        /// Output file path
+        output: PathBuf,
+        /// Settings file with the credentials
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        /// Format the mission is written in
+        #[arg(long, value_enum, default_value_t = MissionFormat::Binary)]
+        format: MissionFormat,
+    },
+    /// List the missions of the litchi cloud account
+    List {
+        /// Settings file with the credentials
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        /// Print the missions as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Delete a mission from the litchi cloud
+    Delete {
+        /// Object id of the mission
+        id: String,
+        /// Settings file with the credentials
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        /// Delete without asking for confirmation
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Check a CSV file without uploading it, failing if it has errors or likely mistakes
+    Validate {
+        /// CSV file to check
+        input: PathBuf,
+    },
+    /// Compare two missions, each either a CSV or a binary mission file
+    Diff {
+        a: PathBuf,
+        b: PathBuf,
+        /// Print the differences as JSON
+        #[arg(long)]
+        json: bool,
     },
 }
 
@@ -48,7 +172,9 @@ pub enum Command {
 async fn main() {
     let cli = Cli::parse();
 
-    let formatter = tracing_subscriber::fmt().with_env_filter(EnvFilter::from_default_env());
+    let formatter = tracing_subscriber::fmt().with_env_filter(log_filter(
+        &std::env::var(EnvFilter::DEFAULT_ENV).unwrap_or_default(),
+    ));
 
     if cli.pretty_logs {
         formatter
@@ -59,35 +185,582 @@ async fn main() {
         formatter.init();
     }
 
-    match cli.command {
-        Command::ConvertCsv { input, output } => {
-            let mission = read_csv_to_mission(&input);
-            std::fs::write(output, mission.to_binary()).expect("Could not write mission to file");
+    if let Err(err) = run(cli.command).await {
+        eprintln!("Error: {err:#}");
+        std::process::exit(1);
+    }
+}
+
+async fn run(command: Command) -> anyhow::Result<()> {
+    match command {
+        Command::ConvertCsv {
+            input,
+            output,
+            strip_pois,
+            config,
+            settings,
+        } => {
+            let config = read_config(config.as_deref())?;
+            let mut mission = read_csv_to_mission(&input)?;
+            config.apply(&mut mission, &settings)?;
+            if strip_pois {
+                mission.strip_pois();
+            }
+            std::fs::write(&output, mission.try_to_binary()?)
+                .with_context(|| format!("Could not write {}", output.display()))?;
+        }
+        Command::ConvertDir { input, output } => {
+            if !convert_dir(&input, &output)? {
+                bail!("Not all missions could be converted");
+            }
         }
         Command::Upload {
             input,
             config,
             name,
+            settings,
         } => {
-            let mission = read_csv_to_mission(&input);
-            let config: UploadConfig = serde_json::from_str(
-                &std::fs::read_to_string(config).expect("Could not read upload configuration"),
-            )
-            .unwrap();
+            let config = read_config(config.as_deref())?;
+            let mut mission = read_csv_to_mission(&input)?;
+            config.apply(&mut mission, &settings)?;
 
-            let api = LitchiApi::login(&config.username, &config.password)
-                .await
-                .expect("Authentication with litchi api failed");
+            info!(%mission, "Uploading mission");
+            let api = login(config).await?;
             api.upload(&mission, &name)
                 .await
-                .expect("Failed to uploda mission to Litchi");
-            api.sync_devices().await.expect("Failed to sync deices");
+                .context("Failed to upload mission to Litchi")?;
+            api.sync_devices().await.context("Failed to sync devices")?;
         }
+        Command::Download {
+            id,
+            output,
+            config,
+            format,
+        } => {
+            let api = login(read_config(config.as_deref())?).await?;
+            let id = ObjectId::new(id);
+            let mission = api
+                .missions()
+                .await
+                .context("Failed to list missions")?
+                .into_iter()
+                .find(|mission| mission.object_id == id)
+                .with_context(|| format!("No mission with id {}", id.as_str()))?;
+            match format {
+                // The file as stored, re-encoding could change fields the parser doesn't keep
+                MissionFormat::Binary => {
+                    let bytes = api
+                        .download_mission_bytes(&mission)
+                        .await
+                        .context("Failed to download mission")?;
+                    eprintln!("Downloaded mission {:?}", mission.name);
+                    std::fs::write(&output, bytes)?
+                }
+                MissionFormat::Csv => {
+                    let litchi_mission = api
+                        .download_mission(&mission)
+                        .await
+                        .context("Failed to download mission")?;
+                    eprintln!("Downloaded mission {:?}", mission.name);
+                    write_to_csv(&litchi_mission, csv::Writer::from_path(&output)?)?
+                }
+            }
+        }
+        Command::List { config, json } => {
+            let api = login(read_config(config.as_deref())?).await?;
+            let missions = api.missions().await.context("Failed to list missions")?;
+            print!("{}", format_missions(&missions, json));
+        }
+        Command::Delete { id, config, yes } => {
+            if !yes && !confirm(&format!("Delete mission {id}?")) {
+                bail!("Aborted");
+            }
+
+            let api = login(read_config(config.as_deref())?).await?;
+            api.delete_mission(ObjectId::new(id.as_str()))
+                .await
+                .with_context(|| format!("Failed to delete mission {id}"))?;
+            eprintln!("Deleted mission {id}");
+        }
+        Command::Validate { input } => {
+            let csv = std::fs::read(&input)
+                .with_context(|| format!("Could not read {}", input.display()))?;
+            match validate_csv(&csv) {
+                Ok(mission) => println!(
+                    "OK: {} waypoints, {} POIs",
+                    mission.waypoints().len(),
+                    mission.pois().len()
+                ),
+                Err(issues) => {
+                    for issue in &issues {
+                        println!("{issue}");
+                    }
+                    bail!("Found {} validation issues", issues.len());
+                }
+            }
+        }
+        Command::Diff { a, b, json } => {
+            let differences = read_mission(&a)?.diff(&read_mission(&b)?);
+            print!("{}", format_differences(&differences, json));
+        }
+    }
+
+    Ok(())
+}
+
+/// Logs in with the credentials of the settings file, falling back to `$LITCHI_USERNAME` and
+/// `$LITCHI_PASSWORD`
+async fn login(config: CliConfig) -> anyhow::Result<LitchiApi> {
+    let username = config
+        .username
+        .or_else(|| std::env::var("LITCHI_USERNAME").ok())
+        .context("No username in the configuration or $LITCHI_USERNAME")?;
+    let password = config
+        .password
+        .or_else(|| std::env::var("LITCHI_PASSWORD").ok())
+        .context("No password in the configuration or $LITCHI_PASSWORD")?;
+    let api_config = LitchiApiConfig {
+        base_url: config
+            .base_url
+            .unwrap_or_else(|| LitchiApiConfig::default().base_url),
+        ..Default::default()
+    };
+
+    LitchiApi::login_with_config(&api_config, &username, &password)
+        .await
+        .context("Authentication with litchi api failed")
+}
+
+/// Asks a yes/no question on the terminal, anything but "y" or "yes" is a no
+fn confirm(question: &str) -> bool {
+    eprint!("{question} [y/N] ");
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Reads the settings file, or uses the defaults without one
+fn read_config(path: Option<&Path>) -> anyhow::Result<CliConfig> {
+    let Some(path) = path else {
+        return Ok(CliConfig::default());
+    };
+
+    let config = std::fs::read_to_string(path)
+        .with_context(|| format!("Could not read configuration {}", path.display()))?;
+    serde_json::from_str(&config)
+        .with_context(|| format!("Invalid configuration {}", path.display()))
+}
+
+impl CliConfig {
+    /// Applies the mission settings to the mission, preferring the flags over the file
+    fn apply(&self, mission: &mut LitchiMission, flags: &MissionSettings) -> anyhow::Result<()> {
+        let file = &self.mission;
+        let config = mission.config_mut();
+        if let Some(speed) = flags.cruising_speed.or(file.cruising_speed) {
+            config.cruising_speed = speed;
+        }
+        if let Some(speed) = flags.rc_speed.or(file.rc_speed) {
+            config.rc_speed = speed;
+        }
+
+        // An interval given as flag replaces either interval of the file
+        let (time_interval, distance_interval) =
+            if flags.photo_time_interval.is_some() || flags.photo_distance_interval.is_some() {
+                (flags.photo_time_interval, flags.photo_distance_interval)
+            } else {
+                (file.photo_time_interval, file.photo_distance_interval)
+            };
+        if let Some(interval) = time_interval {
+            config.photo_interval = Some(PhotoInterval::Time(interval));
+        } else if let Some(interval) = distance_interval {
+            config.photo_interval = Some(PhotoInterval::Distance(interval));
+        }
+
+        if let Some(reference) = flags.altitude_mode.or(file.altitude_mode) {
+            let altitude_mode = match reference {
+                AltitudeReference::AboveTakeoff => AltitudeMode::Absolute,
+                AltitudeReference::AboveGround => AltitudeMode::AboveGround,
+            };
+            mission.set_altitude_mode(altitude_mode);
+        }
+
+        if let Some(geofence) = &self.geofence {
+            let bbox = (
+                Coordinate(geofence.south_west.0, geofence.south_west.1),
+                Coordinate(geofence.north_east.0, geofence.north_east.1),
+            );
+            let mode = if geofence.clamp {
+                GeofenceMode::Clamp
+            } else {
+                GeofenceMode::Remove
+            };
+            let outside = mission
+                .clamp_to_geofence(bbox, mode)
+                .context("Could not apply geofence")?;
+            if outside > 0 {
+                info!(outside, ?mode, "Waypoints were outside of the geofence");
+            }
+        }
+
+        Ok(())
     }
 }
 
-fn read_csv_to_mission(csv_path: &Path) -> LitchiMission {
-    let csv_file = csv::Reader::from_path(csv_path).expect("Failed to create reader over file");
+/// Builds the log filter from the `RUST_LOG` directives, falling back to `info` when none are set
+fn log_filter(directives: &str) -> EnvFilter {
+    EnvFilter::builder()
+        .with_default_directive(LevelFilter::INFO.into())
+        .parse_lossy(directives)
+}
 
-    read_from_csv(csv_file).expect("Failed to parse CSV")
+/// Reads a binary mission file, or a CSV if it lacks the binary signature
+fn read_mission(path: &Path) -> anyhow::Result<LitchiMission> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Could not read {}", path.display()))?;
+
+    if binary_format::has_signature(&bytes) {
+        LitchiMission::from_binary(&bytes)
+            .with_context(|| format!("Failed to parse binary mission {}", path.display()))
+    } else {
+        read_from_csv(csv::Reader::from_reader(bytes.as_slice()))
+            .with_context(|| format!("Failed to parse CSV {}", path.display()))
+    }
+}
+
+fn format_differences(differences: &[Difference], json: bool) -> String {
+    if json {
+        let differences: Vec<_> = differences
+            .iter()
+            .map(|difference| {
+                serde_json::json!({
+                    "path": difference.path,
+                    "left": difference.left,
+                    "right": difference.right,
+                })
+            })
+            .collect();
+        serde_json::to_string_pretty(&differences).expect("Differences are valid JSON") + "\n"
+    } else {
+        differences
+            .iter()
+            .map(|difference| format!("{difference}\n"))
+            .collect()
+    }
+}
+
+/// Reads and checks the mission, or lists its errors and [`LitchiMission::validation_notes`].
+/// Errors of a row are prefixed with its row number in a spreadsheet, where the header is row 1,
+/// unless they already name the line of the file they are in.
+fn validate_csv(csv: &[u8]) -> Result<LitchiMission, Vec<String>> {
+    let row_count = csv::Reader::from_reader(csv).records().count();
+    let mission = read_from_csv_collecting(csv::Reader::from_reader(csv)).map_err(|errors| {
+        errors
+            .into_iter()
+            .map(|(index, err)| {
+                let located = matches!(
+                    err,
+                    LitchiError::FieldParse { .. } | LitchiError::ConflictingPhotoInterval { .. }
+                );
+                // Errors of the whole mission are reported after the last row
+                if index < row_count && !located {
+                    format!("row {}: {err}", index + 2)
+                } else {
+                    err.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+    })?;
+
+    let notes = mission.validation_notes();
+    if notes.is_empty() {
+        Ok(mission)
+    } else {
+        Err(notes.iter().map(ToString::to_string).collect())
+    }
+}
+
+/// Table of the object id, name and location of the missions, or a JSON array of them
+fn format_missions(missions: &[Mission], json: bool) -> String {
+    if json {
+        let missions: Vec<_> = missions
+            .iter()
+            .map(|mission| {
+                serde_json::json!({
+                    "object_id": mission.object_id.as_str(),
+                    "name": mission.name,
+                    "location": [mission.location.0, mission.location.1],
+                    "user_id": mission.user_id.as_str(),
+                    "file": { "name": mission.file.name, "url": mission.file.url },
+                    "metadata": mission.metadata,
+                })
+            })
+            .collect();
+        serde_json::to_string_pretty(&missions).expect("Missions are valid JSON") + "\n"
+    } else {
+        let id_width = missions
+            .iter()
+            .map(|mission| mission.object_id.as_str().len())
+            .chain([2])
+            .max()
+            .unwrap_or_default();
+        let name_width = missions
+            .iter()
+            .map(|mission| mission.name.chars().count())
+            .chain([4])
+            .max()
+            .unwrap_or_default();
+
+        let mut table = format!("{:id_width$}  {:name_width$}  LOCATION\n", "ID", "NAME");
+        for mission in missions {
+            table += &format!(
+                "{:id_width$}  {:name_width$}  {:.6}, {:.6}\n",
+                mission.object_id.as_str(),
+                mission.name,
+                mission.location.0,
+                mission.location.1
+            );
+        }
+        table
+    }
+}
+
+/// Converts all `.csv` files of the input directory into `.mission` files in the output
+/// directory, logging failing files instead of stopping. Returns whether all files converted.
+fn convert_dir(input: &Path, output: &Path) -> anyhow::Result<bool> {
+    let mut csv_paths = vec![];
+    for entry in std::fs::read_dir(input)
+        .with_context(|| format!("Could not read input directory {}", input.display()))?
+    {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "csv") {
+            csv_paths.push(path);
+        }
+    }
+    csv_paths.sort();
+    std::fs::create_dir_all(output)
+        .with_context(|| format!("Could not create output directory {}", output.display()))?;
+
+    let inputs = csv_paths
+        .iter()
+        .map(|path| {
+            let name = path
+                .file_stem()
+                .expect("CSV files have a name")
+                .to_string_lossy()
+                .into_owned();
+            let file = std::fs::File::open(path)
+                .with_context(|| format!("Could not open {}", path.display()))?;
+            Ok((name, file))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let mut success = true;
+    for (name, result) in convert::batch_csv_to_binary(inputs) {
+        match result {
+            Ok(binary) => {
+                std::fs::write(output.join(format!("{name}.mission")), binary)
+                    .with_context(|| format!("Could not write mission {name}"))?;
+                info!(name, "Converted mission");
+            }
+            Err(error) => {
+                error!(name, %error, "Failed to convert mission");
+                success = false;
+            }
+        }
+    }
+
+    Ok(success)
+}
+
+fn read_csv_to_mission(csv_path: &Path) -> anyhow::Result<LitchiMission> {
+    let csv_file = csv::Reader::from_path(csv_path)
+        .with_context(|| format!("Could not open {}", csv_path.display()))?;
+
+    read_from_csv(csv_file).with_context(|| format!("Failed to parse CSV {}", csv_path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_files() {
+        const TEST_MISSION_CSV: &str = include_str!("../../litchitool/test/litchi_mission.csv");
+
+        let directory =
+            std::env::temp_dir().join(format!("litchi-cli-diff-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let csv_path = directory.join("a.csv");
+        std::fs::write(&csv_path, TEST_MISSION_CSV).unwrap();
+
+        // Same mission, as binary with the second waypoint raised
+        let mut lines: Vec<String> = TEST_MISSION_CSV.lines().map(str::to_string).collect();
+        let mut fields: Vec<&str> = lines[2].split(',').collect();
+        fields[2] = "35";
+        lines[2] = fields.join(",");
+        let changed = read_from_csv(csv::Reader::from_reader(lines.join("\n").as_bytes())).unwrap();
+        let binary_path = directory.join("b.mission");
+        std::fs::write(&binary_path, changed.to_binary()).unwrap();
+
+        let differences = read_mission(&csv_path)
+            .unwrap()
+            .diff(&read_mission(&binary_path).unwrap());
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(
+            format_differences(&differences, false),
+            "waypoints[1].altitude: 30.0 -> 35.0\n"
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&format_differences(&differences, true)).unwrap();
+        assert_eq!(json[0]["path"], "waypoints[1].altitude");
+    }
+
+    #[test]
+    fn test_convert_dir() {
+        const TEST_MISSION_CSV: &str = include_str!("../../litchitool/test/litchi_mission.csv");
+
+        let directory =
+            std::env::temp_dir().join(format!("litchi-cli-convert-dir-{}", std::process::id()));
+        let input = directory.join("csv");
+        let output = directory.join("missions");
+        std::fs::create_dir_all(&input).unwrap();
+        std::fs::write(input.join("good.csv"), TEST_MISSION_CSV).unwrap();
+        std::fs::write(input.join("bad.csv"), "latitude\nnot a number\n").unwrap();
+        std::fs::write(input.join("notes.txt"), "not a mission").unwrap();
+
+        let success = convert_dir(&input, &output).unwrap();
+        let mut written: Vec<_> = std::fs::read_dir(&output)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        written.sort();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert!(!success);
+        assert_eq!(written, ["good.mission"]);
+    }
+
+    #[test]
+    fn test_full_config() {
+        const TEST_MISSION_CSV: &[u8] = include_bytes!("../../litchitool/test/litchi_mission.csv");
+
+        let config: CliConfig = serde_json::from_str(
+            r#"{
+                "username": "pilot",
+                "base_url": "https://parse.example.com",
+                "cruising_speed": 6.5,
+                "rc_speed": 12,
+                "altitude_mode": "above_ground",
+                "photo_distance_interval": 20,
+                "geofence": { "south_west": [30, -20], "north_east": [60, 20], "clamp": true }
+            }"#,
+        )
+        .expect("Could not parse full config");
+        assert_eq!(config.password, None);
+
+        let mut mission = read_from_csv(csv::Reader::from_reader(TEST_MISSION_CSV)).unwrap();
+        let total = mission.waypoints().len();
+        let flags = MissionSettings {
+            rc_speed: Some(10.),
+            ..Default::default()
+        };
+        config.apply(&mut mission, &flags).unwrap();
+
+        assert_eq!(mission.config().cruising_speed, 6.5);
+        assert_eq!(mission.config().rc_speed, 10.);
+        assert_eq!(
+            mission.config().photo_interval,
+            Some(PhotoInterval::Distance(20.))
+        );
+        assert!(mission
+            .waypoints()
+            .iter()
+            .all(|waypoint| waypoint.altitude_mode == AltitudeMode::AboveGround));
+        assert_eq!(mission.waypoints().len(), total);
+        assert!(mission.waypoints().iter().all(|waypoint| {
+            (30. ..=60.).contains(&waypoint.coordinate.0)
+                && (-20. ..=20.).contains(&waypoint.coordinate.1)
+        }));
+    }
+
+    #[test]
+    fn test_log_filter_defaults_to_info() {
+        assert_eq!(log_filter("").to_string(), "info");
+    }
+
+    #[test]
+    fn test_log_filter_honors_directives() {
+        assert_eq!(
+            log_filter("litchi_api=trace").to_string(),
+            "litchi_api=trace"
+        );
+    }
+
+    #[test]
+    fn test_format_missions() {
+        let missions = vec![Mission {
+            object_id: ObjectId::new("abc123"),
+            location: Coordinate(48.137154, 11.576124),
+            name: "Marienplatz".to_string(),
+            user_id: ObjectId::new("user1"),
+            file: litchi_api::MissionFile {
+                name: "marienplatz.bin".to_string(),
+                url: "https://example.com/marienplatz.bin".to_string(),
+            },
+            metadata: Default::default(),
+        }];
+
+        assert_eq!(
+            format_missions(&missions, false),
+            "ID      NAME         LOCATION\n\
+             abc123  Marienplatz  48.137154, 11.576124\n"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&format_missions(&missions, true)).unwrap();
+        assert_eq!(json[0]["object_id"], "abc123");
+        assert_eq!(
+            json[0]["location"],
+            serde_json::json!([48.137154, 11.576124])
+        );
+    }
+
+    #[test]
+    fn test_error_context() {
+        let err = read_mission(Path::new("does/not/exist.csv")).unwrap_err();
+
+        assert!(format!("{err:#}").starts_with("Could not read does/not/exist.csv: "));
+    }
+
+    #[test]
+    fn test_validate_csv() {
+        const TEST_MISSION_CSV: &[u8] = include_bytes!("../../litchitool/test/litchi_mission.csv");
+
+        let mission = validate_csv(TEST_MISSION_CSV).expect("Test mission is valid");
+        assert_eq!(mission.waypoints().len(), 15);
+
+        let mut lines: Vec<_> = std::str::from_utf8(TEST_MISSION_CSV)
+            .unwrap()
+            .lines()
+            .collect();
+        let original = lines[2];
+        let broken = original.replacen(',', ",north,", 1);
+        lines[2] = &broken;
+        let issues = validate_csv(lines.join("\n").as_bytes()).unwrap_err();
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].starts_with("row 3: "), "{}", issues[0]);
+
+        let unparsable = original.replacen(',', ",north", 1);
+        lines[2] = &unparsable;
+        let issues = validate_csv(lines.join("\n").as_bytes()).unwrap_err();
+        assert!(
+            issues[0].starts_with("Could not parse the longitude column of CSV line 3: "),
+            "{}",
+            issues[0]
+        );
+    }
 }
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use litchitool::mission::LitchiMission;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = LitchiMission::from_binary(data);
+});
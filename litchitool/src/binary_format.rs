@@ -0,0 +1,593 @@
+use std::{
+    fmt::Write,
+    ops::{Deref, DerefMut},
+};
+
+use bytes::{Bytes, BytesMut};
+use num_enum::TryFromPrimitive;
+use tracing::{debug, warn};
+
+use crate::{
+    error::LitchiError,
+    mission::{
+        Action, AltitudeMode, Coordinate, FinishAction, GimbalPitchMode, HeadingMode,
+        LitchiMission, MissionConfig, PathMode, PhotoInterval, Waypoint, BINARY_VERSION, POI,
+    },
+};
+
+/// Litchi file format signature ("lchm")
+pub(crate) const SIGNATURE: i32 = 1818454125;
+
+/// Whether the data starts with the binary mission signature, to tell it apart from a CSV
+pub fn has_signature(bytes: &[u8]) -> bool {
+    bytes.starts_with(&SIGNATURE.to_be_bytes())
+}
+
+/// Buffer the binary format is encoded into, optionally recording labelled sections
+#[derive(Default)]
+pub(crate) struct BinaryWriter {
+    buf: BytesMut,
+    labels: Option<Vec<(usize, String)>>,
+}
+
+impl BinaryWriter {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buf: BytesMut::with_capacity(capacity),
+            labels: None,
+        }
+    }
+
+    /// Starts a new labelled section at the current offset
+    pub(crate) fn label(&mut self, label: impl FnOnce() -> String) {
+        if let Some(labels) = &mut self.labels {
+            labels.push((self.buf.len(), label()));
+        }
+    }
+
+    pub(crate) fn finish(self) -> Bytes {
+        self.buf.freeze()
+    }
+}
+
+impl Deref for BinaryWriter {
+    type Target = BytesMut;
+
+    fn deref(&self) -> &Self::Target {
+        &self.buf
+    }
+}
+
+impl DerefMut for BinaryWriter {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.buf
+    }
+}
+
+/// Bounds-checked big endian reader over a binary mission
+struct BinaryReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+macro_rules! read_fn {
+    ($name:ident, $type:ty) => {
+        fn $name(&mut self, field: &str) -> Result<$type, LitchiError> {
+            const SIZE: usize = std::mem::size_of::<$type>();
+            Ok(<$type>::from_be_bytes(
+                self.take(SIZE, field)?
+                    .try_into()
+                    .expect("Slice has the type's size"),
+            ))
+        }
+    };
+}
+
+impl<'a> BinaryReader<'a> {
+    fn take(&mut self, len: usize, field: &str) -> Result<&'a [u8], LitchiError> {
+        let slice = self
+            .bytes
+            .get(self.offset..self.offset + len)
+            .ok_or_else(|| {
+                LitchiError::BinaryFormatError(format!(
+                    "Unexpected end of data reading {field} at offset {}",
+                    self.offset
+                ))
+            })?;
+        self.offset += len;
+        Ok(slice)
+    }
+
+    read_fn!(read_i16, i16);
+    read_fn!(read_i32, i32);
+    read_fn!(read_f32, f32);
+    read_fn!(read_f64, f64);
+
+    /// Reads an element count, checking that `min_element_size` bytes per element are left
+    fn read_count(&mut self, field: &str, min_element_size: usize) -> Result<usize, LitchiError> {
+        let count = self.read_i32(field)?;
+        let count = usize::try_from(count)
+            .map_err(|_| LitchiError::BinaryFormatError(format!("Negative {field}: {count}")))?;
+
+        if count.saturating_mul(min_element_size) > self.bytes.len() - self.offset {
+            Err(LitchiError::BinaryFormatError(format!(
+                "{field} of {count} exceeds the remaining data"
+            )))?;
+        }
+
+        Ok(count)
+    }
+
+    /// Reads a photo interval written as (time, distance), with -1 marking the unused one
+    fn read_interval(&mut self) -> Result<Option<PhotoInterval>, LitchiError> {
+        let time = self.read_f32("photo time interval")?;
+        let distance = self.read_f32("photo distance interval")?;
+
+        Ok(if time > 0. {
+            Some(PhotoInterval::Time(time))
+        } else if distance > 0. {
+            Some(PhotoInterval::Distance(distance))
+        } else {
+            None
+        })
+    }
+}
+
+fn parse_enum<T>(value: T::Primitive) -> Result<T, LitchiError>
+where
+    T: TryFromPrimitive,
+    T::Primitive: std::fmt::Display,
+{
+    T::try_from_primitive(value)
+        .map_err(|err| LitchiError::TryFromPrimitiveError(err.number.to_string()))
+}
+
+/// Size of the encoded mission config, up to the number of waypoints
+const HEADER_SIZE: usize = 40;
+/// Size of an encoded waypoint block without its actions
+const WAYPOINT_BASE_SIZE: usize = 56;
+/// Size of an encoded action
+const ACTION_SIZE: usize = 8;
+/// Size of an encoded POI position
+const POI_SIZE: usize = 20;
+/// Size of a waypoint's altitude mode, altitude and POI index
+const WAYPOINT_ALTITUDE_SIZE: usize = 10;
+/// Size of a POI's altitude mode and altitude
+const POI_ALTITUDE_SIZE: usize = 6;
+/// Size of an encoded photo interval
+const INTERVAL_SIZE: usize = 8;
+
+impl LitchiMission {
+    /// Exact length of [`LitchiMission::to_binary`]
+    pub(crate) fn binary_size(&self) -> usize {
+        let waypoints: usize = self
+            .waypoints()
+            .iter()
+            .map(|waypoint| {
+                WAYPOINT_BASE_SIZE
+                    + ACTION_SIZE * waypoint.actions.len()
+                    + WAYPOINT_ALTITUDE_SIZE
+                    + INTERVAL_SIZE
+            })
+            .sum();
+
+        // The counts of waypoints and POIs, the magic numbers and the mission photo interval
+        HEADER_SIZE
+            + 4
+            + waypoints
+            + 4
+            + self.pois().len() * (POI_SIZE + POI_ALTITUDE_SIZE)
+            + 12
+            + INTERVAL_SIZE
+    }
+
+    /// Hex dump of [`LitchiMission::to_binary`], with each line labelled with the section it
+    /// encodes. Fails like [`LitchiMission::try_to_binary`] if the mission doesn't fit into the
+    /// format.
+    pub fn to_annotated_hex(&self) -> Result<String, LitchiError> {
+        const BYTES_PER_LINE: usize = 16;
+
+        let mut writer = BinaryWriter {
+            buf: BytesMut::new(),
+            labels: Some(vec![]),
+        };
+        self.encode(&mut writer)?;
+
+        let labels = writer.labels.take().unwrap_or_default();
+        let bytes = writer.finish();
+        let label_width = labels
+            .iter()
+            .map(|(_, label)| label.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut dump = String::new();
+        for (index, (start, label)) in labels.iter().enumerate() {
+            let end = labels.get(index + 1).map_or(bytes.len(), |(end, _)| *end);
+
+            for (line, chunk) in bytes[*start..end].chunks(BYTES_PER_LINE).enumerate() {
+                let label = if line == 0 { label.as_str() } else { "" };
+                let hex = chunk
+                    .iter()
+                    .map(|byte| format!("{byte:02x}"))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                writeln!(
+                    dump,
+                    "{:08x}  {label:label_width$}  {hex}",
+                    start + line * BYTES_PER_LINE
+                )
+                .expect("Writing to a string can't fail");
+            }
+        }
+
+        Ok(dump)
+    }
+
+    /// Parses a mission from the litchi binary mission format, as written by
+    /// [`LitchiMission::to_binary`]
+    ///
+    /// The format carries no name or other metadata: the photo intervals are its last section.
+    /// A mission's name only lives in the Litchi cloud's mission object.
+    pub fn from_binary(bytes: &[u8]) -> Result<LitchiMission, LitchiError> {
+        let mut reader = BinaryReader { bytes, offset: 0 };
+
+        let signature = reader.read_i32("signature")?;
+        if signature != SIGNATURE {
+            Err(LitchiError::BinaryFormatError(format!(
+                "Invalid signature {signature:#x}"
+            )))?;
+        }
+
+        let heading_mode = reader.read_i32("heading mode")?;
+        let finish_action = reader.read_i32("finish action")?;
+        let path_mode = reader.read_i32("path mode")?;
+        let cruising_speed = reader.read_f32("cruising speed")?;
+        let rc_speed = reader.read_f32("rc speed")?;
+        let n_repeat = reader.read_i32("number of repetitions")?;
+        let version = reader.read_i16("version")?;
+        reader.take(10, "padding")?;
+
+        let config = MissionConfig {
+            heading_mode: parse_enum::<HeadingMode>(heading_mode)?,
+            finish_action: parse_enum::<FinishAction>(finish_action)?,
+            path_mode: parse_enum::<PathMode>(path_mode)?,
+            cruising_speed,
+            rc_speed,
+            n_repeat,
+            version,
+            photo_interval: None,
+            initial_heading: None,
+        };
+
+        match version {
+            BINARY_VERSION => read_v11(reader, config),
+            found => Err(LitchiError::UnsupportedVersion { found }),
+        }
+    }
+}
+
+fn read_v11(
+    mut reader: BinaryReader,
+    mut config: MissionConfig,
+) -> Result<LitchiMission, LitchiError> {
+    let n_waypoints = reader.read_count("number of waypoints", WAYPOINT_BASE_SIZE)?;
+    let mut waypoints = Vec::with_capacity(n_waypoints);
+
+    for _ in 0..n_waypoints {
+        let altitude = reader.read_f32("waypoint altitude")?;
+        let turn_mode = reader.read_i32("waypoint turn mode")?;
+        let heading = reader.read_f32("waypoint heading")?;
+        let speed = reader.read_f32("waypoint speed")?;
+        let stay_time = reader.read_i16("waypoint stay time")?;
+        let max_reach_time = reader.read_i16("waypoint max reach time")?;
+        let latitude = reader.read_f64("waypoint latitude")?;
+        let longitude = reader.read_f64("waypoint longitude")?;
+        let curve_size = reader.read_f32("waypoint curve size")?;
+        let gimbal_mode = reader.read_i32("waypoint gimbal mode")?;
+        let gimbal_pitch_angle = reader.read_i32("waypoint gimbal pitch angle")?;
+        let n_actions = reader.read_count("number of waypoint actions", 8)?;
+        let repeat_actions = reader.read_i32("waypoint action repetitions")?;
+
+        let actions = (0..n_actions)
+            .map(|_| {
+                let action_n = reader.read_i32("action type")?;
+                let param = reader.read_i32("action parameter")?;
+                Action::from_idx_and_param(action_n, param)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        waypoints.push(Waypoint {
+            coordinate: Coordinate(latitude, longitude),
+            altitude,
+            heading,
+            curve_size,
+            // Not part of the binary format
+            rotation_dir: 0,
+            gimbal_mode: parse_enum::<GimbalPitchMode>(gimbal_mode)?,
+            gimbal_pitch_angle,
+            altitude_mode: AltitudeMode::Absolute,
+            speed,
+            poi_index: None,
+            actions,
+            photo_interval: None,
+            turn_mode,
+            stay_time,
+            max_reach_time,
+            repeat_actions,
+        });
+    }
+
+    let n_pois = reader.read_count("number of POIs", POI_SIZE)?;
+    let mut pois = Vec::with_capacity(n_pois);
+
+    for _ in 0..n_pois {
+        let latitude = reader.read_f64("POI latitude")?;
+        let longitude = reader.read_f64("POI longitude")?;
+        let altitude = reader.read_f32("POI altitude")?;
+
+        pois.push(POI {
+            coordinate: Coordinate(latitude, longitude),
+            altitude,
+            altitude_mode: AltitudeMode::Absolute,
+        });
+    }
+
+    for (index, waypoint) in waypoints.iter_mut().enumerate() {
+        let altitude_mode = reader.read_i16("waypoint altitude mode")?;
+        waypoint.altitude_mode = parse_enum::<AltitudeMode>(altitude_mode)?;
+        // Written from the same field as the altitude of the waypoint block, see `encode`
+        let altitude = reader.read_f32("waypoint altitude")?;
+        if altitude.to_bits() != waypoint.altitude.to_bits() {
+            warn!(
+                waypoint = index,
+                altitude = waypoint.altitude,
+                mode_altitude = altitude,
+                "Waypoint altitudes disagree, keeping the one of the waypoint block"
+            );
+        }
+        let poi_index = reader.read_i32("waypoint POI index")?;
+        waypoint.poi_index = match poi_index {
+            -1 => None,
+            index => Some(usize::try_from(index).map_err(|_| {
+                LitchiError::BinaryFormatError(format!("Invalid POI index {index}"))
+            })?),
+        };
+    }
+
+    for poi in &mut pois {
+        let altitude_mode = reader.read_i16("POI altitude mode")?;
+        poi.altitude_mode = parse_enum::<AltitudeMode>(altitude_mode)?;
+        poi.altitude = reader.read_f32("POI altitude")?;
+    }
+
+    let magic_numbers = [
+        reader.read_i32("magic number")?,
+        reader.read_i32("magic number")?,
+        reader.read_i32("magic number")?,
+    ];
+    if magic_numbers != [8, 8, 0] {
+        debug!(?magic_numbers, "Unexpected magic numbers in binary mission");
+    }
+
+    config.photo_interval = reader.read_interval()?;
+    for waypoint in &mut waypoints {
+        waypoint.photo_interval = reader.read_interval()?;
+    }
+
+    if reader.offset != reader.bytes.len() {
+        Err(LitchiError::BinaryFormatError(format!(
+            "{} trailing bytes after the mission",
+            reader.bytes.len() - reader.offset
+        )))?;
+    }
+
+    LitchiMission::new(waypoints, pois, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv_format;
+
+    const TEST_MISSION_CSV: &[u8] = include_bytes!("../test/litchi_mission.csv");
+
+    fn test_mission() -> LitchiMission {
+        csv_format::read_from_csv(csv::Reader::from_reader(TEST_MISSION_CSV))
+            .expect("Could not parse test mission from csv")
+    }
+
+    #[test]
+    fn test_read_version_11() {
+        let mission = test_mission();
+
+        let parsed = LitchiMission::from_binary(&mission.to_binary())
+            .expect("Could not parse version 11 binary");
+
+        assert_eq!(parsed.config().version, 11);
+        assert_eq!(parsed.waypoints().len(), mission.waypoints().len());
+        assert_eq!(
+            parsed.waypoints()[0].coordinate,
+            mission.waypoints()[0].coordinate
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mission = test_mission();
+
+        let parsed = LitchiMission::from_binary(&mission.to_binary())
+            .expect("Could not parse binary of the test mission");
+
+        assert_eq!(parsed, mission);
+    }
+
+    #[test]
+    fn test_binary_size() {
+        for mission in [test_mission(), LitchiMission::default()] {
+            assert_eq!(mission.to_binary().len(), mission.binary_size());
+        }
+    }
+
+    #[test]
+    fn test_photo_intervals_round_trip() {
+        let mission = test_mission();
+        let intervals = [
+            None,
+            Some(PhotoInterval::Time(1.5)),
+            Some(PhotoInterval::Distance(10.)),
+        ];
+        let waypoints = mission
+            .waypoints()
+            .iter()
+            .cloned()
+            .zip(intervals.iter().cycle())
+            .map(|(waypoint, photo_interval)| Waypoint {
+                photo_interval: photo_interval.clone(),
+                ..waypoint
+            })
+            .collect();
+        let config = MissionConfig {
+            photo_interval: Some(PhotoInterval::Time(2.)),
+            ..mission.config().clone()
+        };
+        let mission = LitchiMission::new(waypoints, mission.pois().clone(), config).unwrap();
+
+        let parsed = LitchiMission::from_binary(&mission.to_binary())
+            .expect("Could not parse binary with photo intervals");
+
+        assert_eq!(
+            parsed.config().photo_interval,
+            Some(PhotoInterval::Time(2.))
+        );
+        let parsed_intervals: Vec<_> = parsed
+            .waypoints()
+            .iter()
+            .map(|waypoint| waypoint.photo_interval.clone())
+            .collect();
+        let expected: Vec<_> = intervals
+            .iter()
+            .cycle()
+            .take(mission.waypoints().len())
+            .cloned()
+            .collect();
+        assert_eq!(parsed_intervals, expected);
+    }
+
+    #[test]
+    fn test_annotated_hex() {
+        insta::assert_snapshot!(test_mission().to_annotated_hex().unwrap());
+
+        let mut unsupported = test_mission();
+        unsupported.config_mut().version = 10;
+        assert!(matches!(
+            unsupported.to_annotated_hex(),
+            Err(LitchiError::UnsupportedVersion { found: 10 })
+        ));
+    }
+
+    #[test]
+    fn test_altitude_modes() {
+        for altitude_mode in [AltitudeMode::Absolute, AltitudeMode::AboveGround] {
+            let mission = crate::mission::MissionBuilder::new()
+                .waypoint(48.0, 11.0, 30.)
+                .altitude_mode(altitude_mode)
+                .waypoint(48.001, 11.0, 45.5)
+                .altitude_mode(altitude_mode)
+                .build()
+                .unwrap();
+
+            insta::assert_snapshot!(
+                format!("altitude_mode_{altitude_mode:?}"),
+                mission.to_annotated_hex().unwrap()
+            );
+
+            let parsed = LitchiMission::from_binary(&mission.to_binary()).unwrap();
+            for (parsed, waypoint) in parsed.waypoints().iter().zip(mission.waypoints()) {
+                assert_eq!(parsed.altitude_mode, altitude_mode);
+                assert_eq!(parsed.altitude, waypoint.altitude);
+            }
+        }
+    }
+
+    #[test]
+    fn test_no_trailing_metadata() {
+        let mut binary = test_mission().to_binary().to_vec();
+        LitchiMission::from_binary(&binary).expect("Binary should be fully consumed");
+
+        binary.extend_from_slice(b"mission name");
+
+        assert!(matches!(
+            LitchiMission::from_binary(&binary),
+            Err(LitchiError::BinaryFormatError(_))
+        ));
+    }
+
+    #[test]
+    fn test_fuzz_seeds() {
+        let seeds: [&[u8]; 3] = [
+            include_bytes!("../fuzz/corpus/from_binary/empty"),
+            include_bytes!("../fuzz/corpus/from_binary/signature_only"),
+            include_bytes!("../fuzz/corpus/from_binary/truncated_mid_waypoint"),
+        ];
+
+        for seed in seeds {
+            assert!(LitchiMission::from_binary(seed).is_err());
+        }
+    }
+
+    #[test]
+    fn test_truncated_binary() {
+        let binary = test_mission().to_binary();
+
+        for len in 0..binary.len() {
+            assert!(
+                LitchiMission::from_binary(&binary[..len]).is_err(),
+                "Binary truncated to {len} bytes should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn test_random_binary() {
+        // xorshift, so the inputs are the same on every run
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        let binary = test_mission().to_binary();
+
+        for _ in 0..1000 {
+            let len = next() as usize % 256;
+            let mut random = SIGNATURE.to_be_bytes().to_vec();
+            random.extend((0..len).map(|_| next() as u8));
+            assert!(LitchiMission::from_binary(&random).is_err());
+
+            // Corrupted valid missions may still parse, but must not panic
+            let mut corrupted = binary.to_vec();
+            for _ in 0..4 {
+                let offset = next() as usize % corrupted.len();
+                corrupted[offset] = next() as u8;
+            }
+            let _ = LitchiMission::from_binary(&corrupted);
+        }
+    }
+
+    #[test]
+    fn test_unknown_version() {
+        let mut binary = test_mission().to_binary().to_vec();
+        // The version follows the signature, three enums, two speeds and the repetitions
+        binary[28..30].copy_from_slice(&12i16.to_be_bytes());
+
+        let result = LitchiMission::from_binary(&binary);
+
+        assert!(matches!(
+            result,
+            Err(LitchiError::UnsupportedVersion { found: 12 })
+        ));
+    }
+}
@@ -0,0 +1,55 @@
+use std::io::Read;
+
+use bytes::Bytes;
+
+use crate::{csv_format::read_from_csv, error::LitchiError};
+
+/// Converts a Litchi CSV mission to the binary mission format
+pub fn csv_to_binary<R: Read>(reader: R) -> Result<Bytes, LitchiError> {
+    read_from_csv(csv::Reader::from_reader(reader))?.try_to_binary()
+}
+
+/// Converts each named CSV mission to the binary format with [`csv_to_binary`]. A failing input
+/// doesn't stop the others, every result is returned with its name in input order.
+pub fn batch_csv_to_binary<R: Read>(
+    inputs: impl IntoIterator<Item = (String, R)>,
+) -> Vec<(String, Result<Bytes, LitchiError>)> {
+    inputs
+        .into_iter()
+        .map(|(name, reader)| {
+            let result = csv_to_binary(reader);
+            (name, result)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mission::LitchiMission;
+
+    const TEST_MISSION_CSV: &[u8] = include_bytes!("../test/litchi_mission.csv");
+
+    #[test]
+    fn test_batch_csv_to_binary() {
+        let truncated = &TEST_MISSION_CSV[..TEST_MISSION_CSV.len() / 2];
+
+        let results = batch_csv_to_binary([
+            ("truncated.csv".to_string(), truncated),
+            ("mission.csv".to_string(), TEST_MISSION_CSV),
+        ]);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, "truncated.csv");
+        assert!(results[0].1.is_err());
+        assert_eq!(results[1].0, "mission.csv");
+        let binary = results[1].1.as_ref().expect("Valid CSV should convert");
+        assert_eq!(
+            LitchiMission::from_binary(binary)
+                .unwrap()
+                .waypoints()
+                .len(),
+            15
+        );
+    }
+}
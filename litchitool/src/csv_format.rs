@@ -1,146 +1,986 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
 pub use csv;
-use csv::Reader;
-use tracing::Level;
+use csv::{Reader, StringRecord, Writer};
+use tracing::{warn, Level};
 
 use crate::{
     error::LitchiError,
     mission::{
-        Action, AltitudeMode, Coordinate, GimbalPitchMode, LitchiMission, MissionConfig,
-        PhotoInterval, Waypoint, POI,
+        Action, AltitudeMode, Coordinate, FinishAction, GimbalPitchMode, HeadingMode,
+        LitchiMission, MissionConfig, PathMode, PhotoInterval, Waypoint, MAX_ACTIONS, POI,
     },
 };
 
+const ACTIONS_OFFSET: usize = 8;
+const ACTIONS_COUNT: usize = MAX_ACTIONS;
+const ACTIONS_END: usize = ACTIONS_OFFSET + ACTIONS_COUNT * 2;
+
+/// Header of the 46-column Litchi CSV layout
+const FULL_HEADER: [&str; 46] = [
+    "latitude",
+    "longitude",
+    "altitude(m)",
+    "heading(deg)",
+    "curvesize(m)",
+    "rotationdir",
+    "gimbalmode",
+    "gimbalpitchangle",
+    "actiontype1",
+    "actionparam1",
+    "actiontype2",
+    "actionparam2",
+    "actiontype3",
+    "actionparam3",
+    "actiontype4",
+    "actionparam4",
+    "actiontype5",
+    "actionparam5",
+    "actiontype6",
+    "actionparam6",
+    "actiontype7",
+    "actionparam7",
+    "actiontype8",
+    "actionparam8",
+    "actiontype9",
+    "actionparam9",
+    "actiontype10",
+    "actionparam10",
+    "actiontype11",
+    "actionparam11",
+    "actiontype12",
+    "actionparam12",
+    "actiontype13",
+    "actionparam13",
+    "actiontype14",
+    "actionparam14",
+    "actiontype15",
+    "actionparam15",
+    "altitudemode",
+    "speed(m/s)",
+    "poi_latitude",
+    "poi_longitude",
+    "poi_altitude(m)",
+    "poi_altitudemode",
+    "photo_timeinterval",
+    "photo_distinterval",
+];
+
+/// Header of the reduced [`ColumnSet::Minimal`] layout
+const MINIMAL_HEADER: [&str; 3] = ["latitude", "longitude", "altitude(m)"];
+
+/// Which columns [`write_to_csv_with_options`] emits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColumnSet {
+    /// Only latitude, longitude and altitude, with its own header
+    Minimal,
+    /// The full 46-column Litchi layout, as read by [`read_from_csv`]
+    #[default]
+    Full,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CsvWriteOptions {
+    pub columns: ColumnSet,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CsvReadOptions {
+    /// Configuration of the read mission. With [`HeadingMode::Custom`] the CSV headings are kept
+    /// as is instead of facing the waypoint's POI.
+    pub config: MissionConfig,
+}
+
+/// First field of a config record, see [`MissionConfig::to_csv_record`]
+const CONFIG_RECORD_MARKER: &str = "config";
+
 macro_rules! parse_chunk {
     ($record:expr => $($name:ident ($type:ty) $idx:expr),+) => {
         $(
             let $name: $type = $record.get($idx).ok_or(LitchiError::CsvMissingField($idx))?.parse()?;
         )+
+    };
+    // Reports parse errors with the file line and column name they are in
+    ($record:expr, line = $line:expr => $($name:ident ($type:ty) $idx:expr),+) => {
+        $(
+            let $name: $type = $record
+                .get($idx)
+                .ok_or(LitchiError::CsvMissingField($idx))?
+                .parse::<$type>()
+                .map_err(|err| LitchiError::FieldParse {
+                    line: $line,
+                    column: FULL_HEADER[$idx],
+                    source: Box::new(err.into()),
+                })?;
+        )+
+    };
+}
+
+/// Reads a mission from the CSV layout exported by Litchi's Mission Hub.
+///
+/// If the reader has headers, the columns are found by their names, see
+/// [`read_from_csv_by_header`], so reordered columns and extra columns of other Litchi versions
+/// are fine. Only without headers the 46 columns are read by their position.
+///
+/// Litchi has no separate "above takeoff" or terrain altitude column: the `altitude(m)` and
+/// `poi_altitude(m)` values are interpreted according to the `altitudemode` and
+/// `poi_altitudemode` columns, see [`AltitudeMode`].
+///
+/// A leading UTF-8 BOM and rows without any values are ignored. Waypoints with
+/// [`GimbalPitchMode::FocusPOI`] but without a POI get their gimbal mode disabled.
+pub fn read_from_csv<R: Read>(reader: Reader<R>) -> Result<LitchiMission, LitchiError> {
+    read_from_csv_with_options(reader, CsvReadOptions::default())
+}
+
+pub fn read_from_csv_with_options<R: Read>(
+    mut reader: Reader<R>,
+    options: CsvReadOptions,
+) -> Result<LitchiMission, LitchiError> {
+    read_records(full_layout_records(&mut reader)?, options)
+}
+
+/// Reads a mission from a Litchi CSV, finding the columns by their header instead of their
+/// position, which [`read_from_csv`] does for all readers with headers. Columns may come in any
+/// order, and besides the names of the [`FULL_HEADER`] layout these alternative spellings are
+/// recognized (ignoring case and surrounding whitespace):
+///
+/// | Column               | Alternatives                                 |
+/// |----------------------|----------------------------------------------|
+/// | `altitude(m)`        | `altitude`, `altitude (m)`                   |
+/// | `heading(deg)`       | `heading`, `heading (deg)`                   |
+/// | `curvesize(m)`       | `curvesize`, `curve_size`, `curvesize (m)`   |
+/// | `gimbalmode`         | `gimbal_mode`                                |
+/// | `gimbalpitchangle`   | `gimbal_pitch_angle`, `gimbalpitch`          |
+/// | `altitudemode`       | `altitude_mode`                              |
+/// | `speed(m/s)`         | `speed`, `speed (m/s)`                       |
+/// | `poi_altitude(m)`    | `poi_altitude`, `poi_altitude (m)`           |
+/// | `poi_altitudemode`   | `poi_altitude_mode`                          |
+/// | `photo_timeinterval` | `photo_time_interval`                        |
+/// | `photo_distinterval` | `photo_dist_interval`, `photo_distance_interval` |
+///
+/// Only `latitude`, `longitude` and `altitude(m)` are required, otherwise this fails with
+/// [`LitchiError::CsvMissingColumn`]. Missing action columns mean no action, missing photo
+/// intervals no interval, and all other missing columns are 0. Unknown columns are ignored.
+pub fn read_from_csv_by_header<R: Read>(
+    mut reader: Reader<R>,
+) -> Result<LitchiMission, LitchiError> {
+    let columns = header_columns(reader.headers()?)?;
+    read_records(
+        rearrange_records(reader.records(), columns),
+        CsvReadOptions::default(),
+    )
+}
+
+/// Records in the [`FULL_HEADER`] layout, arranged by the header names if the reader has headers
+fn full_layout_records<R: Read>(
+    reader: &mut Reader<R>,
+) -> Result<Box<dyn Iterator<Item = Result<StringRecord, csv::Error>> + '_>, LitchiError> {
+    if reader.has_headers() {
+        let columns = header_columns(reader.headers()?)?;
+        Ok(Box::new(rearrange_records(reader.records(), columns)))
+    } else {
+        Ok(Box::new(reader.records()))
+    }
+}
+
+/// Index of the column of each [`FULL_HEADER`] column in `headers`, if it has one
+fn header_columns(headers: &StringRecord) -> Result<Vec<Option<usize>>, LitchiError> {
+    let headers: Vec<String> = headers.iter().map(normalize_header).collect();
+
+    let columns = FULL_HEADER
+        .iter()
+        .map(|column| {
+            let aliases = HEADER_ALIASES
+                .iter()
+                .find(|(name, _)| name == column)
+                .map_or(&[][..], |(_, aliases)| aliases);
+
+            headers
+                .iter()
+                .position(|header| header == column || aliases.contains(&header.as_str()))
+        })
+        .collect::<Vec<_>>();
+
+    for (column, name) in columns.iter().zip(FULL_HEADER).take(3) {
+        if column.is_none() {
+            Err(LitchiError::CsvMissingColumn(name.to_string()))?;
+        }
     }
+
+    Ok(columns)
+}
+
+/// Rearranges records into the [`FULL_HEADER`] layout, filling in the defaults of missing columns
+fn rearrange_records<'r>(
+    records: impl Iterator<Item = Result<StringRecord, csv::Error>> + 'r,
+    columns: Vec<Option<usize>>,
+) -> impl Iterator<Item = Result<StringRecord, csv::Error>> + 'r {
+    records.map(move |record| {
+        let record = record?;
+        let mut rearranged: StringRecord = columns
+            .iter()
+            .zip(FULL_HEADER)
+            .map(|(column, name)| match column {
+                Some(column) => record.get(*column).unwrap_or_default(),
+                None if name.starts_with("actiontype") || name.starts_with("photo_") => "-1",
+                None => "0",
+            })
+            .collect();
+        // Keeps the file line for error messages
+        rearranged.set_position(record.position().cloned());
+        Ok(rearranged)
+    })
 }
 
-pub fn read_from_csv<R: Read>(mut reader: Reader<R>) -> Result<LitchiMission, LitchiError> {
+/// Alternative names of the [`FULL_HEADER`] columns, see [`read_from_csv_by_header`]
+const HEADER_ALIASES: [(&str, &[&str]); 11] = [
+    ("altitude(m)", &["altitude", "altitude (m)"]),
+    ("heading(deg)", &["heading", "heading (deg)"]),
+    (
+        "curvesize(m)",
+        &["curvesize", "curve_size", "curvesize (m)"],
+    ),
+    ("gimbalmode", &["gimbal_mode"]),
+    ("gimbalpitchangle", &["gimbal_pitch_angle", "gimbalpitch"]),
+    ("altitudemode", &["altitude_mode"]),
+    ("speed(m/s)", &["speed", "speed (m/s)"]),
+    ("poi_altitude(m)", &["poi_altitude", "poi_altitude (m)"]),
+    ("poi_altitudemode", &["poi_altitude_mode"]),
+    ("photo_timeinterval", &["photo_time_interval"]),
+    (
+        "photo_distinterval",
+        &["photo_dist_interval", "photo_distance_interval"],
+    ),
+];
+
+fn normalize_header(header: &str) -> String {
+    header.trim().to_lowercase()
+}
+
+/// Reads a mission like [`read_from_csv`], but continues past failing rows to report the errors
+/// of all of them, together with the index of the row (not counting the header). Errors of the
+/// mission as a whole are only checked if all rows could be read, and are reported at the index
+/// after the last row. A header without a required column is reported at index 0.
+pub fn read_from_csv_collecting<R: Read>(
+    mut reader: Reader<R>,
+) -> Result<LitchiMission, Vec<(usize, LitchiError)>> {
+    let options = CsvReadOptions::default();
+    let keep_headings = options.config.heading_mode == HeadingMode::Custom;
     let mut waypoints: Vec<Waypoint> = vec![];
     let mut pois: Vec<POI> = vec![];
+    let mut errors = vec![];
+    let mut row_count = 0;
 
-    for (record_index, record) in reader.records().enumerate() {
+    let records = full_layout_records(&mut reader).map_err(|err| vec![(0, err)])?;
+    for (record_index, record) in records.enumerate() {
         let row_span = tracing::span!(Level::TRACE, "Parsing row of CSV", row = record_index);
         let _enter = row_span.enter();
-        let record = record?;
-        if record.len() != 46 {
-            Err(LitchiError::IncorrectRecordLength(record.len(), 46))?;
+        row_count = record_index + 1;
+        match read_row(record_index, record, keep_headings, &mut pois) {
+            Ok(Some(waypoint)) => waypoints.push(waypoint),
+            Ok(None) => {}
+            Err(err) => errors.push((record_index, err)),
         }
+    }
 
-        const ACTIONS_OFFSET: usize = 8;
-        const ACTIONS_COUNT: usize = 15;
-        const ACTIONS_END: usize = ACTIONS_OFFSET + ACTIONS_COUNT * 2;
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    LitchiMission::new(waypoints, pois, options.config).map_err(|err| vec![(row_count, err)])
+}
 
-        parse_chunk!(record =>
-            latitude             (f64) 0,
-            longitude            (f64) 1,
-            altitude             (f32) 2,
-            heading              (f32) 3,
-            curve_size           (f32) 4,
-            rotation_dir         (i32) 5,
-            gimbal_mode          (i32) 6,
-            gimbal_pitch_angle   (i32) 7,
-            altitude_mode       (i16) ACTIONS_END,
-            speed               (f32) ACTIONS_END + 1,
-            poi_latitude        (f64) ACTIONS_END + 2,
-            poi_longitude       (f64) ACTIONS_END + 3,
-            poi_altitude        (f32) ACTIONS_END + 4,
-            poi_altitude_mode   (i16) ACTIONS_END + 5,
-            photo_time_interval (f32) ACTIONS_END + 6,
-            photo_distance_interval (f32) ACTIONS_END + 7
-        );
+/// Reads the mission from records in the [`FULL_HEADER`] layout
+fn read_records(
+    records: impl Iterator<Item = Result<StringRecord, csv::Error>>,
+    options: CsvReadOptions,
+) -> Result<LitchiMission, LitchiError> {
+    let keep_headings = options.config.heading_mode == HeadingMode::Custom;
+    let mut waypoints: Vec<Waypoint> = vec![];
+    let mut pois: Vec<POI> = vec![];
 
-        let mut heading = heading;
+    for (record_index, record) in records.enumerate() {
+        let row_span = tracing::span!(Level::TRACE, "Parsing row of CSV", row = record_index);
+        let _enter = row_span.enter();
+        if let Some(waypoint) = read_row(record_index, record, keep_headings, &mut pois)? {
+            waypoints.push(waypoint);
+        }
+    }
 
-        let gimbal_mode = GimbalPitchMode::try_from(gimbal_mode)
-            .map_err(|err| LitchiError::TryFromPrimitiveError(err.number.to_string()))?;
-        let altitude_mode = AltitudeMode::try_from(altitude_mode)
+    LitchiMission::new(waypoints, pois, options.config)
+}
+
+/// Reads the waypoint of a record in the [`FULL_HEADER`] layout, `None` for empty records
+fn read_row(
+    row: usize,
+    record: Result<StringRecord, csv::Error>,
+    keep_headings: bool,
+    pois: &mut Vec<POI>,
+) -> Result<Option<Waypoint>, LitchiError> {
+    let record = record?;
+    // Spreadsheets like to end their exports with empty rows
+    if record.iter().all(|field| field.trim().is_empty()) {
+        return Ok(None);
+    }
+    if record.len() != 46 {
+        Err(LitchiError::IncorrectRecordLength(record.len(), 46))?;
+    }
+
+    // Records not read from a file count their lines from the first one
+    let line = record
+        .position()
+        .map_or(row as u64 + 1, |position| position.line());
+    read_waypoint(line, &record, keep_headings, pois).map(Some)
+}
+
+/// Reads the waypoint of a CSV record starting on the given line, adding its POI to `pois`
+/// unless it is already known
+fn read_waypoint(
+    line: u64,
+    record: &StringRecord,
+    keep_headings: bool,
+    pois: &mut Vec<POI>,
+) -> Result<Waypoint, LitchiError> {
+    parse_chunk!(record, line = line =>
+        latitude             (f64) 0,
+        longitude            (f64) 1,
+        altitude             (f32) 2,
+        heading              (f32) 3,
+        curve_size           (f32) 4,
+        rotation_dir         (i32) 5,
+        gimbal_mode          (i32) 6,
+        gimbal_pitch_angle   (i32) 7,
+        altitude_mode       (i16) ACTIONS_END,
+        speed               (f32) ACTIONS_END + 1,
+        poi_latitude        (f64) ACTIONS_END + 2,
+        poi_longitude       (f64) ACTIONS_END + 3,
+        poi_altitude        (f32) ACTIONS_END + 4,
+        poi_altitude_mode   (i16) ACTIONS_END + 5,
+        photo_time_interval (f32) ACTIONS_END + 6,
+        photo_distance_interval (f32) ACTIONS_END + 7
+    );
+
+    let mut heading = heading;
+
+    let mut gimbal_mode = GimbalPitchMode::try_from(gimbal_mode)
+        .map_err(|err| LitchiError::TryFromPrimitiveError(err.number.to_string()))?;
+    let altitude_mode = AltitudeMode::try_from(altitude_mode)
+        .map_err(|err| LitchiError::TryFromPrimitiveError(err.number.to_string()))?;
+    let photo_interval = match (photo_time_interval > 0., photo_distance_interval > 0.) {
+        (true, true) => Err(LitchiError::ConflictingPhotoInterval { line })?,
+        (true, false) => Some(PhotoInterval::Time(photo_time_interval)),
+        (false, true) => Some(PhotoInterval::Distance(photo_distance_interval)),
+        (false, false) => None,
+    };
+
+    let actions = (0..ACTIONS_COUNT)
+        .map(|action_i| -> Result<Option<Action>, LitchiError> {
+            parse_chunk!(record, line = line =>
+                action_type (i32) ACTIONS_OFFSET + action_i * 2,
+                action_param (i32) ACTIONS_OFFSET + 1 + action_i * 2
+            );
+
+            Ok(match action_type {
+                -1 => None,
+                n => Some(Action::from_idx_and_param(n, action_param)?),
+            })
+        })
+        .filter_map(|res| match res {
+            Ok(Some(action)) => Some(Ok(action)),
+            Ok(None) => None,
+            Err(error) => Some(Err(error)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let poi = {
+        let poi_altitude_mode = AltitudeMode::try_from(poi_altitude_mode)
             .map_err(|err| LitchiError::TryFromPrimitiveError(err.number.to_string()))?;
-        let photo_time_interval = Some(photo_time_interval).filter(|interval| *interval > 0.);
-        let photo_distance_interval =
-            Some(photo_distance_interval).filter(|interval| *interval > 0.);
-
-        let actions = (0..ACTIONS_COUNT)
-            .map(|action_i| -> Result<Option<Action>, LitchiError> {
-                parse_chunk!(record =>
-                    action_type (i32) ACTIONS_OFFSET + action_i * 2,
-                    action_param (i32) ACTIONS_OFFSET + 1 + action_i * 2
-                );
-
-                Ok(match action_type {
-                    -1 => None,
-                    0 => Some(Action::StayFor(action_param as f32 / 1000.)),
-                    1 => Some(Action::TakePhoto),
-                    2 => Some(Action::StartRecording),
-                    3 => Some(Action::StopRecording),
-                    4 => Some(Action::RotateAircraft(action_param)),
-                    5 => Some(Action::TiltCamera(action_param)),
-                    n => Err(LitchiError::InvalidActionType(n))?,
-                })
+
+        if poi_longitude != 0. && poi_latitude != 0. && poi_altitude != 0. {
+            Some(POI {
+                coordinate: Coordinate(poi_latitude, poi_longitude),
+                altitude: poi_altitude,
+                altitude_mode: poi_altitude_mode,
             })
-            .filter_map(|res| match res {
-                Ok(Some(action)) => Some(Ok(action)),
-                Ok(None) => None,
-                Err(error) => Some(Err(error)),
+        } else {
+            None
+        }
+    };
+
+    // Litchi can't focus a POI that isn't there, so don't let it guess
+    if gimbal_mode == GimbalPitchMode::FocusPOI && poi.is_none() {
+        warn!("Waypoint focuses a POI but has none, disabling its gimbal mode");
+        gimbal_mode = GimbalPitchMode::Disabled;
+    }
+
+    let coordinates = Coordinate(latitude, longitude);
+
+    // A POI right at the waypoint has no direction, keep the CSV heading then
+    if let Some(poi) = poi
+        .as_ref()
+        .filter(|poi| !keep_headings && poi.coordinate != coordinates)
+    {
+        let bearing = coordinates.heading_towards(&poi.coordinate) as f32;
+        if bearing.is_finite() {
+            heading = bearing;
+        }
+    }
+
+    let poi_index = poi.map(|poi| {
+        pois.iter()
+            .position(|search_poi| &poi == search_poi)
+            .unwrap_or_else(|| {
+                pois.push(poi);
+                pois.len() - 1
             })
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let poi = {
-            let poi_altitude_mode = AltitudeMode::try_from(poi_altitude_mode)
-                .map_err(|err| LitchiError::TryFromPrimitiveError(err.number.to_string()))?;
-
-            if poi_longitude != 0. && poi_latitude != 0. && poi_altitude != 0. {
-                Some(POI {
-                    coordinate: Coordinate(poi_latitude, poi_longitude),
-                    altitude: poi_altitude,
-                    altitude_mode: poi_altitude_mode,
-                })
-            } else {
-                None
-            }
+    });
+
+    Ok(Waypoint {
+        coordinate: coordinates,
+        altitude,
+        altitude_mode,
+        heading,
+        curve_size,
+        gimbal_mode,
+        gimbal_pitch_angle,
+        speed,
+        poi_index,
+        actions,
+        photo_interval,
+        rotation_dir,
+        ..Default::default()
+    })
+}
+
+impl MissionConfig {
+    /// Encodes the config as a single CSV record, for an extended dialect that puts it on a line
+    /// before the waypoint rows. Litchi itself doesn't know this record, and since it is shorter
+    /// than the waypoint rows it is best read with a flexible [`csv::ReaderBuilder`].
+    ///
+    /// The fields are the marker `config`, then the heading mode, finish action and path mode as
+    /// their numeric values, the cruising speed, rc speed, number of repetitions, version, and the
+    /// photo time and distance interval with -1 for the unused one, like in waypoint rows.
+    pub fn to_csv_record(&self) -> StringRecord {
+        let (photo_time_interval, photo_distance_interval) = match self.photo_interval {
+            Some(PhotoInterval::Time(time)) => (time, -1.),
+            Some(PhotoInterval::Distance(distance)) => (-1., distance),
+            None => (-1., -1.),
         };
 
-        let coordinates = Coordinate(latitude, longitude);
+        StringRecord::from(vec![
+            CONFIG_RECORD_MARKER.to_string(),
+            (self.heading_mode as i32).to_string(),
+            (self.finish_action as i32).to_string(),
+            (self.path_mode as i32).to_string(),
+            self.cruising_speed.to_string(),
+            self.rc_speed.to_string(),
+            self.n_repeat.to_string(),
+            self.version.to_string(),
+            photo_time_interval.to_string(),
+            photo_distance_interval.to_string(),
+        ])
+    }
 
-        if let Some(poi) = &poi {
-            heading = coordinates.heading_towards(&poi.coordinate) as f32;
+    /// Parses a record written by [`MissionConfig::to_csv_record`]
+    pub fn from_csv_record(record: &StringRecord) -> Result<Self, LitchiError> {
+        if record.len() != 10 {
+            Err(LitchiError::IncorrectRecordLength(record.len(), 10))?;
+        }
+        if &record[0] != CONFIG_RECORD_MARKER {
+            Err(LitchiError::InvalidConfigRecord(record[0].to_string()))?;
         }
 
-        let poi_index = poi.map(|poi| {
-            pois.iter()
-                .position(|search_poi| &poi == search_poi)
-                .unwrap_or_else(|| {
-                    pois.push(poi);
-                    pois.len() - 2
-                })
-        });
-
-        waypoints.push(Waypoint {
-            coordinate: coordinates,
-            altitude,
-            altitude_mode,
-            heading,
-            curve_size,
-            gimbal_mode,
-            gimbal_pitch_angle,
-            speed,
-            poi_index,
-            actions,
-            turn_mode: 0,
-            photo_interval: photo_time_interval
-                .map(PhotoInterval::Time)
-                .or_else(|| photo_distance_interval.map(PhotoInterval::Distance)),
-            repeat_actions: 1,
-            rotation_dir,
-            stay_time: 3,
-            max_reach_time: 0,
+        parse_chunk!(record =>
+            heading_mode            (i32) 1,
+            finish_action           (i32) 2,
+            path_mode               (i32) 3,
+            cruising_speed          (f32) 4,
+            rc_speed                (f32) 5,
+            n_repeat                (i32) 6,
+            version                 (i16) 7,
+            photo_time_interval     (f32) 8,
+            photo_distance_interval (f32) 9
+        );
+
+        let photo_interval = if photo_time_interval > 0. {
+            Some(PhotoInterval::Time(photo_time_interval))
+        } else if photo_distance_interval > 0. {
+            Some(PhotoInterval::Distance(photo_distance_interval))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            heading_mode: HeadingMode::try_from(heading_mode)
+                .map_err(|err| LitchiError::TryFromPrimitiveError(err.number.to_string()))?,
+            finish_action: FinishAction::try_from(finish_action)
+                .map_err(|err| LitchiError::TryFromPrimitiveError(err.number.to_string()))?,
+            path_mode: PathMode::try_from(path_mode)
+                .map_err(|err| LitchiError::TryFromPrimitiveError(err.number.to_string()))?,
+            cruising_speed,
+            rc_speed,
+            n_repeat,
+            version,
+            photo_interval,
+            // Not part of the config record
+            initial_heading: None,
         })
     }
+}
+
+/// Writes the mission in the full 46-column Litchi CSV layout
+pub fn write_to_csv<W: Write>(
+    mission: &LitchiMission,
+    writer: Writer<W>,
+) -> Result<(), LitchiError> {
+    write_to_csv_with_options(mission, writer, &CsvWriteOptions::default())
+}
+
+pub fn write_to_csv_with_options<W: Write>(
+    mission: &LitchiMission,
+    mut writer: Writer<W>,
+    options: &CsvWriteOptions,
+) -> Result<(), LitchiError> {
+    match options.columns {
+        ColumnSet::Minimal => writer.write_record(MINIMAL_HEADER)?,
+        ColumnSet::Full => writer.write_record(FULL_HEADER)?,
+    }
+
+    for waypoint in mission.waypoints() {
+        let mut record = vec![
+            waypoint.coordinate.0.to_string(),
+            waypoint.coordinate.1.to_string(),
+            waypoint.altitude.to_string(),
+        ];
+
+        if options.columns == ColumnSet::Full {
+            record.extend([
+                waypoint.heading.to_string(),
+                waypoint.curve_size.to_string(),
+                waypoint.rotation_dir.to_string(),
+                (waypoint.gimbal_mode as i32).to_string(),
+                waypoint.gimbal_pitch_angle.to_string(),
+            ]);
+
+            if waypoint.actions.len() > ACTIONS_COUNT {
+                Err(LitchiError::CsvTooManyActions(waypoint.actions.len()))?;
+            }
+            for action_i in 0..ACTIONS_COUNT {
+                let (action_type, action_param) = waypoint
+                    .actions
+                    .get(action_i)
+                    .map_or(Ok((-1, 0)), Action::idx_and_param)?;
+                record.extend([action_type.to_string(), action_param.to_string()]);
+            }
+
+            let poi = waypoint
+                .poi_index
+                .and_then(|index| mission.pois().get(index));
+            let (photo_time_interval, photo_distance_interval) = match waypoint.photo_interval {
+                Some(PhotoInterval::Time(time)) => (time, -1.),
+                Some(PhotoInterval::Distance(distance)) => (-1., distance),
+                None => (-1., -1.),
+            };
+
+            record.extend([
+                (waypoint.altitude_mode as i16).to_string(),
+                waypoint.speed.to_string(),
+                poi.map_or(0., |poi| poi.coordinate.0).to_string(),
+                poi.map_or(0., |poi| poi.coordinate.1).to_string(),
+                poi.map_or(0., |poi| poi.altitude).to_string(),
+                poi.map_or(0, |poi| poi.altitude_mode as i16).to_string(),
+                photo_time_interval.to_string(),
+                photo_distance_interval.to_string(),
+            ]);
+        }
+
+        writer.write_record(&record)?;
+    }
+
+    writer.flush().map_err(csv::Error::from)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_MISSION_CSV: &[u8] = include_bytes!("../test/litchi_mission.csv");
+
+    #[test]
+    fn test_read_altitude_modes() {
+        const ALTITUDE_MODES_CSV: &[u8] =
+            include_bytes!("../test/litchi_mission_altitude_modes.csv");
+
+        let mission = read_from_csv(Reader::from_reader(ALTITUDE_MODES_CSV))
+            .expect("Could not parse altitude mode mission from csv");
+
+        let modes: Vec<_> = mission
+            .waypoints()
+            .iter()
+            .map(|waypoint| (waypoint.altitude, waypoint.altitude_mode))
+            .collect();
+        assert_eq!(
+            modes,
+            [
+                (40., AltitudeMode::AboveGround),
+                (25., AltitudeMode::Absolute),
+                (60., AltitudeMode::AboveGround)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_focus_poi_without_poi() {
+        let csv = String::from_utf8(TEST_MISSION_CSV.to_vec()).unwrap();
+        let mut lines: Vec<String> = csv.lines().map(str::to_string).collect();
+        let mut fields: Vec<&str> = lines[1].split(',').collect();
+        fields[6] = "1";
+        lines[1] = fields.join(",");
+
+        let mission = read_from_csv(Reader::from_reader(lines.join("\n").as_bytes()))
+            .expect("Could not parse mission from csv");
+
+        let waypoint = &mission.waypoints()[0];
+        assert_eq!(waypoint.poi_index, None);
+        assert_eq!(waypoint.gimbal_mode, GimbalPitchMode::Disabled);
+    }
+
+    #[test]
+    fn test_poi_indices() {
+        let csv = std::str::from_utf8(TEST_MISSION_CSV).unwrap();
+        let mut lines = csv.lines();
+        let header = lines.next().unwrap();
+        let row: Vec<&str> = lines.next().unwrap().split(',').collect();
+        let with_poi = |latitude: &str, longitude: &str, altitude: &str| {
+            let mut fields = row.clone();
+            fields[ACTIONS_END + 2..ACTIONS_END + 6]
+                .copy_from_slice(&[latitude, longitude, altitude, "1"]);
+            fields.join(",")
+        };
+        let csv = [
+            header.to_string(),
+            with_poi("33.62", "5.92", "10"),
+            with_poi("34.5", "6.5", "20"),
+            with_poi("33.62", "5.92", "10"),
+        ]
+        .join("\n");
+
+        let mission = read_from_csv(Reader::from_reader(csv.as_bytes()))
+            .expect("Could not parse mission with POIs");
+
+        assert_eq!(mission.pois().len(), 2);
+        let pois: Vec<_> = mission
+            .waypoints_with_pois()
+            .map(|(_, poi)| poi.map(|poi| (poi.coordinate.clone(), poi.altitude)))
+            .collect();
+        assert_eq!(
+            pois,
+            [
+                Some((Coordinate(33.62, 5.92), 10.)),
+                Some((Coordinate(34.5, 6.5), 20.)),
+                Some((Coordinate(33.62, 5.92), 10.)),
+            ]
+        );
+        let indices: Vec<_> = mission.waypoints().iter().map(|w| w.poi_index).collect();
+        assert_eq!(indices, [Some(0), Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn test_read_bom() {
+        let csv = [b"\xEF\xBB\xBF".as_slice(), TEST_MISSION_CSV].concat();
+
+        let mission = read_from_csv(Reader::from_reader(csv.as_slice()))
+            .expect("Could not parse mission with BOM from csv");
+
+        assert_eq!(mission.waypoints().len(), 15);
+    }
+
+    #[test]
+    fn test_read_trailing_empty_records() {
+        let empty_record = ",".repeat(45);
+        let csv = [
+            TEST_MISSION_CSV,
+            b"\r\n\n",
+            empty_record.as_bytes(),
+            b"\n\n",
+        ]
+        .concat();
+
+        let mission = read_from_csv(Reader::from_reader(csv.as_slice()))
+            .expect("Could not parse mission with trailing empty records from csv");
+
+        assert_eq!(mission.waypoints().len(), 15);
+    }
+
+    #[test]
+    fn test_read_collecting() {
+        let csv = std::str::from_utf8(TEST_MISSION_CSV).unwrap();
+        let mut lines: Vec<String> = csv.lines().map(str::to_string).collect();
+        // Rows 1 and 4, after the header
+        lines[2] = lines[2].replacen("30.035271128950342", "north", 1);
+        lines[5] = lines[5].replacen(",30,0,1000,0,0,0,-1,", ",30,0,1000,0,0,0,9,", 1);
+        let csv = lines.join("\n");
+
+        let errors = read_from_csv_collecting(Reader::from_reader(csv.as_bytes()))
+            .expect_err("Broken rows should be reported");
+
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(
+            errors[0],
+            (
+                1,
+                LitchiError::FieldParse {
+                    line: 3,
+                    column: "latitude",
+                    ..
+                }
+            )
+        ));
+        assert!(matches!(
+            errors[1],
+            (
+                4,
+                LitchiError::InvalidActionType {
+                    action_type: 9,
+                    param: 0
+                }
+            )
+        ));
+        assert!(matches!(
+            read_from_csv(Reader::from_reader(csv.as_bytes())),
+            Err(LitchiError::FieldParse { line: 3, .. })
+        ));
+
+        let mission = read_from_csv_collecting(Reader::from_reader(TEST_MISSION_CSV))
+            .expect("Could not parse test mission from csv");
+        assert_eq!(mission.waypoints().len(), 15);
+    }
 
-    LitchiMission::new(waypoints, pois, MissionConfig::default())
+    #[test]
+    fn test_waypoint_defaults_match_csv() {
+        let mission = read_from_csv(Reader::from_reader(TEST_MISSION_CSV))
+            .expect("Could not parse test mission from csv");
+        let default = Waypoint::default();
+
+        for waypoint in mission.waypoints() {
+            assert_eq!(waypoint.turn_mode, default.turn_mode);
+            assert_eq!(waypoint.stay_time, default.stay_time);
+            assert_eq!(waypoint.max_reach_time, default.max_reach_time);
+            assert_eq!(waypoint.repeat_actions, default.repeat_actions);
+        }
+        assert_eq!(default.stay_time, 3);
+        assert_eq!(default.repeat_actions, 1);
+        assert_eq!(default.coordinate, Coordinate(0., 0.));
+    }
+
+    #[test]
+    fn test_config_record_round_trip() {
+        let config = MissionConfig {
+            heading_mode: HeadingMode::Custom,
+            finish_action: FinishAction::Reverse,
+            path_mode: PathMode::CurvedTurns,
+            cruising_speed: 6.5,
+            rc_speed: 12.,
+            n_repeat: 2,
+            version: 11,
+            photo_interval: Some(PhotoInterval::Distance(25.)),
+            initial_heading: None,
+        };
+
+        let record = config.to_csv_record();
+        assert_eq!(&record[0], "config");
+        assert_eq!(MissionConfig::from_csv_record(&record).unwrap(), config);
+
+        let waypoint_record = StringRecord::from(vec!["0"; 10]);
+        assert!(matches!(
+            MissionConfig::from_csv_record(&waypoint_record),
+            Err(LitchiError::InvalidConfigRecord(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_by_header() {
+        let expected = read_from_csv(Reader::from_reader(TEST_MISSION_CSV)).unwrap();
+
+        // Same columns in reverse order, partly with alternative names
+        let mut reader = Reader::from_reader(TEST_MISSION_CSV);
+        let mut header: Vec<String> = reader
+            .headers()
+            .unwrap()
+            .iter()
+            .map(str::to_string)
+            .collect();
+        header[2] = "Altitude".to_string();
+        header[39] = "speed (m/s)".to_string();
+        let mut output = vec![];
+        {
+            let mut writer = Writer::from_writer(&mut output);
+            writer.write_record(header.iter().rev()).unwrap();
+            for record in reader.records() {
+                writer.write_record(record.unwrap().iter().rev()).unwrap();
+            }
+        }
+
+        for csv in [TEST_MISSION_CSV, output.as_slice()] {
+            let mission = read_from_csv_by_header(Reader::from_reader(csv))
+                .expect("Could not parse mission by header");
+            assert_eq!(mission.diff(&expected), []);
+        }
+    }
+
+    #[test]
+    fn test_read_header_variations() {
+        let expected = read_from_csv(Reader::from_reader(TEST_MISSION_CSV)).unwrap();
+
+        // Newer exports with an additional trailing column
+        let extended: Vec<String> = std::str::from_utf8(TEST_MISSION_CSV)
+            .unwrap()
+            .lines()
+            .enumerate()
+            .map(|(index, line)| {
+                if index == 0 {
+                    format!("{line},notes")
+                } else {
+                    format!("{line},")
+                }
+            })
+            .collect();
+        let mission = read_from_csv(Reader::from_reader(extended.join("\n").as_bytes()))
+            .expect("Could not parse mission with an extra column");
+        assert_eq!(mission.diff(&expected), []);
+
+        // Without a header the columns are read by position
+        let rows = TEST_MISSION_CSV
+            .splitn(2, |byte| *byte == b'\n')
+            .nth(1)
+            .unwrap();
+        let mission = read_from_csv(
+            csv::ReaderBuilder::new()
+                .has_headers(false)
+                .from_reader(rows),
+        )
+        .expect("Could not parse mission without header");
+        assert_eq!(mission.diff(&expected), []);
+
+        assert!(matches!(
+            read_from_csv(Reader::from_reader(&b"lat,lon,alt\n1,2,3\n"[..])),
+            Err(LitchiError::CsvMissingColumn(column)) if column == "latitude"
+        ));
+    }
+
+    #[test]
+    fn test_photo_interval_columns() {
+        let csv = std::str::from_utf8(TEST_MISSION_CSV).unwrap();
+        let with_intervals = |time: &str, distance: &str| {
+            let mut lines: Vec<String> = csv.lines().map(str::to_string).collect();
+            let mut fields: Vec<&str> = lines[3].split(',').collect();
+            fields[ACTIONS_END + 6] = time;
+            fields[ACTIONS_END + 7] = distance;
+            lines[3] = fields.join(",");
+            read_from_csv(Reader::from_reader(lines.join("\n").as_bytes()))
+        };
+
+        assert!(matches!(
+            with_intervals("2", "10"),
+            Err(LitchiError::ConflictingPhotoInterval { line: 4 })
+        ));
+        let mission = with_intervals("0", "-1").unwrap();
+        assert_eq!(mission.waypoints()[2].photo_interval, None);
+        let mission = with_intervals("-1", "10").unwrap();
+        assert_eq!(
+            mission.waypoints()[2].photo_interval,
+            Some(PhotoInterval::Distance(10.))
+        );
+    }
+
+    #[test]
+    fn test_field_parse_error() {
+        let mut lines: Vec<_> = std::str::from_utf8(TEST_MISSION_CSV)
+            .unwrap()
+            .lines()
+            .collect();
+        let broken = lines[3].replacen(",30,", ",thirty,", 1);
+        lines[3] = &broken;
+
+        let err = read_from_csv(Reader::from_reader(lines.join("\n").as_bytes())).unwrap_err();
+
+        assert!(matches!(
+            &err,
+            LitchiError::FieldParse { line: 4, column: "altitude(m)", source }
+                if matches!(**source, LitchiError::ParseFloatError(_))
+        ));
+        assert!(err
+            .to_string()
+            .starts_with("Could not parse the altitude(m) column of CSV line 4: "));
+    }
+
+    #[test]
+    fn test_read_by_header_minimal() {
+        let mission = read_from_csv(Reader::from_reader(TEST_MISSION_CSV)).unwrap();
+        let mut output = vec![];
+        write_to_csv_with_options(
+            &mission,
+            Writer::from_writer(&mut output),
+            &CsvWriteOptions {
+                columns: ColumnSet::Minimal,
+            },
+        )
+        .unwrap();
+
+        let minimal = read_from_csv_by_header(Reader::from_reader(output.as_slice()))
+            .expect("Could not parse minimal CSV by header");
+        assert_eq!(minimal.waypoints().len(), mission.waypoints().len());
+        assert!(minimal
+            .waypoints()
+            .iter()
+            .all(|waypoint| waypoint.actions.is_empty()));
+
+        assert!(matches!(
+            read_from_csv_by_header(Reader::from_reader(b"latitude,longitude\n1,2\n".as_slice())),
+            Err(LitchiError::CsvMissingColumn(column)) if column == "altitude(m)"
+        ));
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        let mission = read_from_csv(Reader::from_reader(TEST_MISSION_CSV))
+            .expect("Could not parse test mission from csv");
+
+        let mut output = vec![];
+        write_to_csv(&mission, Writer::from_writer(&mut output)).expect("Could not write csv");
+
+        let mut reader = Reader::from_reader(output.as_slice());
+        assert_eq!(
+            reader.headers().unwrap(),
+            &csv::StringRecord::from(FULL_HEADER.to_vec())
+        );
+        let reparsed = read_from_csv(reader).expect("Could not parse written csv");
+        assert_eq!(reparsed, mission);
+    }
+
+    #[test]
+    fn test_write_minimal_csv() {
+        let mission = read_from_csv(Reader::from_reader(TEST_MISSION_CSV))
+            .expect("Could not parse test mission from csv");
+
+        let mut output = vec![];
+        write_to_csv_with_options(
+            &mission,
+            Writer::from_writer(&mut output),
+            &CsvWriteOptions {
+                columns: ColumnSet::Minimal,
+            },
+        )
+        .expect("Could not write minimal csv");
+
+        let mut reader = Reader::from_reader(output.as_slice());
+        assert_eq!(
+            reader.headers().unwrap(),
+            &csv::StringRecord::from(MINIMAL_HEADER.to_vec())
+        );
+        let records = reader.records().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(records.len(), mission.waypoints().len());
+        assert!(records.iter().all(|record| record.len() == 3));
+    }
 }
@@ -0,0 +1,162 @@
+use std::fmt;
+
+use crate::mission::LitchiMission;
+
+/// A field that differs between two missions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    /// Path of the field, like `waypoints[3].altitude`
+    pub path: String,
+    /// Value in the left mission, `None` if it has no such waypoint or POI
+    pub left: Option<String>,
+    /// Value in the right mission, `None` if it has no such waypoint or POI
+    pub right: Option<String>,
+}
+
+macro_rules! diff_fields {
+    ($differences:expr, $prefix:expr, $left:expr, $right:expr, $($field:ident),+) => {
+        $(
+            let left = format!("{:?}", $left.$field);
+            let right = format!("{:?}", $right.$field);
+            if left != right {
+                $differences.push(Difference {
+                    path: format!("{}.{}", $prefix, stringify!($field)),
+                    left: Some(left),
+                    right: Some(right),
+                });
+            }
+        )+
+    };
+}
+
+impl LitchiMission {
+    /// Lists the fields of the config, waypoints and POIs that differ from `other`
+    pub fn diff(&self, other: &LitchiMission) -> Vec<Difference> {
+        let mut differences = vec![];
+
+        let (left, right) = (self.config(), other.config());
+        diff_fields!(
+            differences,
+            "config",
+            left,
+            right,
+            heading_mode,
+            finish_action,
+            path_mode,
+            cruising_speed,
+            rc_speed,
+            n_repeat,
+            version,
+            photo_interval,
+            initial_heading
+        );
+
+        for index in 0..self.waypoints().len().max(other.waypoints().len()) {
+            let prefix = format!("waypoints[{index}]");
+            match (self.waypoints().get(index), other.waypoints().get(index)) {
+                (Some(left), Some(right)) => {
+                    diff_fields!(
+                        differences,
+                        prefix,
+                        left,
+                        right,
+                        coordinate,
+                        altitude,
+                        heading,
+                        curve_size,
+                        rotation_dir,
+                        gimbal_mode,
+                        gimbal_pitch_angle,
+                        altitude_mode,
+                        speed,
+                        poi_index,
+                        actions,
+                        photo_interval,
+                        turn_mode,
+                        stay_time,
+                        max_reach_time,
+                        repeat_actions
+                    );
+                }
+                (left, right) => differences.push(Difference {
+                    path: prefix,
+                    left: left.map(|waypoint| format!("{:?}", waypoint.coordinate)),
+                    right: right.map(|waypoint| format!("{:?}", waypoint.coordinate)),
+                }),
+            }
+        }
+
+        for index in 0..self.pois().len().max(other.pois().len()) {
+            let prefix = format!("pois[{index}]");
+            match (self.pois().get(index), other.pois().get(index)) {
+                (Some(left), Some(right)) => {
+                    diff_fields!(
+                        differences,
+                        prefix,
+                        left,
+                        right,
+                        coordinate,
+                        altitude,
+                        altitude_mode
+                    );
+                }
+                (left, right) => differences.push(Difference {
+                    path: prefix,
+                    left: left.map(|poi| format!("{:?}", poi.coordinate)),
+                    right: right.map(|poi| format!("{:?}", poi.coordinate)),
+                }),
+            }
+        }
+
+        differences
+    }
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.path,
+            self.left.as_deref().unwrap_or("(missing)"),
+            self.right.as_deref().unwrap_or("(missing)")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mission::{Coordinate, LitchiMission, MissionConfig, Waypoint};
+
+    #[test]
+    fn test_diff_altitude() {
+        let waypoints = vec![
+            Waypoint {
+                coordinate: Coordinate(48.0, 11.0),
+                altitude: 30.,
+                ..Default::default()
+            },
+            Waypoint {
+                coordinate: Coordinate(48.001, 11.0),
+                altitude: 30.,
+                ..Default::default()
+            },
+        ];
+        let left = LitchiMission::new(waypoints.clone(), vec![], MissionConfig::default()).unwrap();
+        let mut changed = waypoints;
+        changed[1].altitude = 45.;
+        changed.push(Waypoint::default());
+        let right = LitchiMission::new(changed, vec![], MissionConfig::default()).unwrap();
+
+        let differences = left.diff(&right);
+
+        assert_eq!(differences.len(), 2);
+        assert_eq!(
+            differences[0].to_string(),
+            "waypoints[1].altitude: 30.0 -> 45.0"
+        );
+        assert_eq!(differences[1].path, "waypoints[2]");
+        assert_eq!(differences[1].left, None);
+        assert!(left.diff(&left).is_empty());
+    }
+}
@@ -6,16 +6,63 @@ pub enum LitchiError {
     CsvError(#[from] csv::Error),
     #[error("Incorrect length of csv record, got {0} but expected {1}")]
     IncorrectRecordLength(usize, usize),
+    #[error("Waypoint has {0} actions, but the CSV format only holds 15")]
+    CsvTooManyActions(usize),
+    #[error("Expected a config record, but it starts with {0:?}")]
+    InvalidConfigRecord(String),
+    #[error("CSV has no {0:?} column")]
+    CsvMissingColumn(String),
     #[error("Field #{0} of the CSV is missing, this error should never appear")]
     CsvMissingField(usize),
+    /// A field of the CSV couldn't be parsed, in the named column of the 1-based file line
+    #[error("Could not parse the {column} column of CSV line {line}: {source}")]
+    FieldParse {
+        line: u64,
+        column: &'static str,
+        source: Box<LitchiError>,
+    },
     #[error("Failed to parse float: {0:?}")]
     ParseFloatError(#[from] std::num::ParseFloatError),
     #[error("Failed to parse Integer: {0:?}")]
     ParseIntError(#[from] std::num::ParseIntError),
-    #[error("Invalid action type {0}")]
-    InvalidActionType(i32),
+    #[error("CSV line {line} has both a photo time and distance interval, Litchi allows only one")]
+    ConflictingPhotoInterval { line: u64 },
+    #[error("Invalid action type {action_type} with parameter {param}")]
+    InvalidActionType { action_type: i32, param: i32 },
+    #[error("Aircraft rotation to {0}° is outside of the supported range")]
+    RotationOutOfRange(i32),
+    #[error("Invalid action sequence: {0}")]
+    InvalidActionSequence(String),
     #[error("Could not convert number to enum value: {0:?}")]
     TryFromPrimitiveError(String),
-    #[error("Invalid mission")]
-    InvalidMission, // TODO: Reason
+    #[error("Invalid binary mission: {0}")]
+    BinaryFormatError(String),
+    #[error("Unsupported binary mission version {found}")]
+    UnsupportedVersion { found: i16 },
+    #[error("Stay time of {0:?} does not fit into the mission format")]
+    StayTimeOutOfRange(std::time::Duration),
+    #[error("Mission has {0} waypoints, more than the binary format can store")]
+    TooManyWaypoints(usize),
+    #[error("Mission has {0} POIs, more than the binary format can store")]
+    TooManyPois(usize),
+    #[error("Waypoint {waypoint} has {count} actions, more than the binary format can store")]
+    TooManyActions { waypoint: usize, count: usize },
+    #[error("POI index {poi_index} of waypoint {waypoint} does not fit into the binary format")]
+    PoiIndexOverflow { waypoint: usize, poi_index: usize },
+    #[error("Got {0} headings for a mission with {1} waypoints")]
+    HeadingCountMismatch(usize, usize),
+    #[error("Error while reading XML: {0}")]
+    XmlError(#[from] quick_xml::Error),
+    #[error("Share payload is not valid base64: {0}")]
+    SharePayloadError(#[from] base64::DecodeError),
+    #[error("Mission has no waypoint {0}")]
+    InvalidWaypointIndex(usize),
+    #[error("Could not render preview: {0}")]
+    PreviewError(String),
+    #[error("Invalid geofence: {0}")]
+    InvalidGeofence(String),
+    #[error("Invalid mission: {0}")]
+    InvalidMission(String),
+    #[error("Waypoint {waypoint} is invalid: {reason}")]
+    InvalidWaypoint { waypoint: usize, reason: String },
 }
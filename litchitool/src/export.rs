@@ -0,0 +1,13 @@
+/// Options of the [`crate::kml`] and [`crate::gpx`] exporters
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Name every waypoint by its index and its distance along the path from the first one, see
+    /// [`crate::mission::LitchiMission::altitude_profile`]. Without labels, KML names waypoints
+    /// by their index only and GPX leaves them unnamed.
+    pub labels: bool,
+}
+
+/// Label of the waypoint at `index` (counted from 1, like in Litchi) for [`ExportOptions::labels`]
+pub(crate) fn waypoint_label(index: usize, distance: f64) -> String {
+    format!("Waypoint {} ({distance:.0} m)", index + 1)
+}
@@ -0,0 +1,86 @@
+use serde_json::{json, Value};
+
+use crate::mission::{Coordinate, LitchiMission};
+
+/// Exports the mission as a GeoJSON `FeatureCollection` for web maps: a `LineString` of the
+/// waypoint path, with the altitude, speed and heading of every waypoint in its properties, and a
+/// `Point` per POI with its altitude
+pub fn to_geojson(mission: &LitchiMission) -> Value {
+    let waypoints = mission.waypoints();
+    let path = json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "LineString",
+            "coordinates": waypoints
+                .iter()
+                .map(|waypoint| position(&waypoint.coordinate))
+                .collect::<Vec<_>>(),
+        },
+        "properties": {
+            "altitude": waypoints.iter().map(|waypoint| waypoint.altitude).collect::<Vec<_>>(),
+            "speed": waypoints.iter().map(|waypoint| waypoint.speed).collect::<Vec<_>>(),
+            "heading": waypoints.iter().map(|waypoint| waypoint.heading).collect::<Vec<_>>(),
+        },
+    });
+
+    let pois = mission.pois().iter().map(|poi| {
+        json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "Point",
+                "coordinates": position(&poi.coordinate),
+            },
+            "properties": {
+                "altitude": poi.altitude,
+            },
+        })
+    });
+
+    json!({
+        "type": "FeatureCollection",
+        "features": std::iter::once(path).chain(pois).collect::<Vec<_>>(),
+    })
+}
+
+/// GeoJSON positions are longitude first
+fn position(coordinate: &Coordinate) -> [f64; 2] {
+    [coordinate.1, coordinate.0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        kml::tests::test_mission,
+        mission::{AltitudeMode, MissionConfig, POI},
+    };
+
+    #[test]
+    fn test_to_geojson() {
+        let mission = LitchiMission::new(
+            test_mission().waypoints().clone(),
+            vec![POI {
+                coordinate: Coordinate(48.0005, 11.001),
+                altitude: 5.,
+                altitude_mode: AltitudeMode::AboveGround,
+            }],
+            MissionConfig::default(),
+        )
+        .unwrap();
+
+        let geojson = to_geojson(&mission);
+
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 1 + mission.pois().len());
+        assert_eq!(
+            features[0]["geometry"]["coordinates"][2],
+            json!([11.002, 48.001])
+        );
+        assert_eq!(features[0]["properties"]["altitude"][2], json!(30.));
+        assert_eq!(
+            features[1]["geometry"]["coordinates"],
+            json!([11.001, 48.0005])
+        );
+        assert_eq!(features[1]["properties"]["altitude"], json!(5.));
+    }
+}
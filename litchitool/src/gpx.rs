@@ -0,0 +1,247 @@
+use std::{
+    fmt::Write,
+    io::{BufReader, Read},
+};
+
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use crate::{
+    error::LitchiError,
+    export::{waypoint_label, ExportOptions},
+    import::{mission_from_points, Elevation, ImportOptions},
+    mission::{Coordinate, LitchiMission},
+};
+
+/// Elements of GPX points, in tracks, routes or standalone
+const POINT_ELEMENTS: [&[u8]; 3] = [b"trkpt", b"rtept", b"wpt"];
+
+/// Exports the waypoints as a GPX 1.1 route.
+///
+/// The `<ele>` elements hold the waypoint altitudes as they are, although GPX elevations are
+/// above sea level. [`from_gpx`] ignores them by default, so importing the export again flattens
+/// the mission to [`ImportOptions::default_altitude`] unless [`ElevationMode::RelativeToFirst`]
+/// is used.
+///
+/// [`ElevationMode::RelativeToFirst`]: crate::import::ElevationMode::RelativeToFirst
+pub fn to_gpx(mission: &LitchiMission) -> String {
+    to_gpx_with_options(mission, &ExportOptions::default())
+}
+
+pub fn to_gpx_with_options(mission: &LitchiMission, options: &ExportOptions) -> String {
+    let mut gpx = String::new();
+
+    // Writing to a String can't fail
+    let _ = write_gpx(&mut gpx, mission, options);
+
+    gpx
+}
+
+fn write_gpx(
+    gpx: &mut String,
+    mission: &LitchiMission,
+    options: &ExportOptions,
+) -> std::fmt::Result {
+    writeln!(gpx, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        gpx,
+        r#"<gpx version="1.1" creator="litchitool" xmlns="http://www.topografix.com/GPX/1/1">"#
+    )?;
+    writeln!(gpx, "  <rte>")?;
+
+    for (index, (waypoint, (distance, _))) in mission
+        .waypoints()
+        .iter()
+        .zip(mission.altitude_profile())
+        .enumerate()
+    {
+        writeln!(
+            gpx,
+            r#"    <rtept lat="{}" lon="{}">"#,
+            waypoint.coordinate.0, waypoint.coordinate.1
+        )?;
+        writeln!(gpx, "      <ele>{}</ele>", waypoint.altitude)?;
+        if options.labels {
+            writeln!(
+                gpx,
+                "      <name>{}</name>",
+                waypoint_label(index, distance)
+            )?;
+        }
+        writeln!(gpx, "    </rtept>")?;
+    }
+
+    writeln!(gpx, "  </rte>")?;
+    writeln!(gpx, "</gpx>")
+}
+
+/// Imports the track, route and waypoint points of a GPX file as waypoints, in the order of the
+/// file. All other settings are defaults. The `<ele>` elevations are above sea level, so they are
+/// only used as configured by [`ImportOptions::elevation`].
+pub fn from_gpx<R: Read>(reader: R) -> Result<LitchiMission, LitchiError> {
+    from_gpx_with_options(reader, &ImportOptions::default())
+}
+
+pub fn from_gpx_with_options<R: Read>(
+    reader: R,
+    options: &ImportOptions,
+) -> Result<LitchiMission, LitchiError> {
+    let mut reader = Reader::from_reader(BufReader::new(reader));
+    reader.config_mut().trim_text(true);
+
+    let mut points = vec![];
+    // The point being read and whether we're inside of its <ele>
+    let mut point: Option<(Coordinate, Option<Elevation>)> = None;
+    let mut in_elevation = false;
+    let mut buf = vec![];
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(element) if is_point(&element) => {
+                point = Some((read_coordinate(&element, points.len())?, None));
+            }
+            // A point without children, so without elevation
+            Event::Empty(element) if is_point(&element) => {
+                points.push((read_coordinate(&element, points.len())?, None));
+            }
+            Event::End(element) if POINT_ELEMENTS.contains(&element.local_name().as_ref()) => {
+                points.extend(point.take());
+            }
+            Event::Start(element) if element.local_name().as_ref() == b"ele" => {
+                in_elevation = point.is_some();
+            }
+            Event::End(element) if element.local_name().as_ref() == b"ele" => {
+                in_elevation = false;
+            }
+            Event::Text(text) if in_elevation => {
+                if let Some((_, altitude)) = &mut point {
+                    *altitude = Some(Elevation::SeaLevel(text.unescape()?.trim().parse()?));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    mission_from_points(points, options)
+}
+
+fn is_point(element: &BytesStart) -> bool {
+    POINT_ELEMENTS.contains(&element.local_name().as_ref())
+}
+
+/// Coordinate of the GPX point `index` from its lat and lon attributes
+fn read_coordinate(element: &BytesStart, index: usize) -> Result<Coordinate, LitchiError> {
+    let lat = required_attribute(element, "lat", index)?.trim().parse()?;
+    let lon = required_attribute(element, "lon", index)?.trim().parse()?;
+
+    Ok(Coordinate(lat, lon))
+}
+
+fn required_attribute(
+    element: &BytesStart,
+    name: &str,
+    index: usize,
+) -> Result<String, LitchiError> {
+    let Some(attribute) = element
+        .try_get_attribute(name)
+        .map_err(quick_xml::Error::from)?
+    else {
+        Err(LitchiError::InvalidWaypoint {
+            waypoint: index,
+            reason: format!("missing {name} attribute"),
+        })?
+    };
+
+    Ok(attribute.unescape_value()?.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        import::{ElevationMode, DEFAULT_ALTITUDE},
+        kml::tests::{profiled_mission, test_mission},
+    };
+
+    const TEST_TRACK_GPX: &[u8] = include_bytes!("../test/track.gpx");
+
+    #[test]
+    fn test_labels() {
+        let gpx = to_gpx_with_options(&test_mission(), &ExportOptions { labels: true });
+
+        insta::assert_snapshot!(gpx);
+    }
+
+    #[test]
+    fn test_from_gpx() {
+        let mission = from_gpx(TEST_TRACK_GPX).expect("Could not import test track");
+
+        let points: Vec<_> = mission
+            .waypoints()
+            .iter()
+            .map(|waypoint| (waypoint.coordinate.clone(), waypoint.altitude))
+            .collect();
+        // The elevations are above sea level, so they are ignored by default
+        assert_eq!(
+            points,
+            vec![
+                (Coordinate(48.1372, 11.5755), DEFAULT_ALTITUDE),
+                (Coordinate(48.1381, 11.5768), DEFAULT_ALTITUDE),
+                (Coordinate(48.139, 11.5781), DEFAULT_ALTITUDE),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_gpx_options() {
+        let options = ImportOptions {
+            default_altitude: 15.,
+            elevation: ElevationMode::RelativeToFirst,
+        };
+        let mission =
+            from_gpx_with_options(TEST_TRACK_GPX, &options).expect("Could not import test track");
+
+        let altitudes: Vec<_> = mission.waypoints().iter().map(|w| w.altitude).collect();
+        assert_eq!(altitudes, [15., 17.5, 15.]);
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let mission = profiled_mission();
+        let gpx = to_gpx(&mission);
+        let altitudes = |mission: &LitchiMission| -> Vec<_> {
+            mission.waypoints().iter().map(|w| w.altitude).collect()
+        };
+
+        // The elevations are ignored by default, flattening the profile
+        let imported = from_gpx(gpx.as_bytes()).expect("Could not import export");
+        assert_eq!(imported.waypoints().len(), mission.waypoints().len());
+        for (imported, original) in imported.waypoints().iter().zip(mission.waypoints()) {
+            assert_eq!(imported.coordinate, original.coordinate);
+        }
+        assert_eq!(altitudes(&imported), [DEFAULT_ALTITUDE; 3]);
+
+        // Starting at the first altitude, relative elevations restore it
+        let options = ImportOptions {
+            default_altitude: 40.,
+            elevation: ElevationMode::RelativeToFirst,
+        };
+        let restored = from_gpx_with_options(gpx.as_bytes(), &options).unwrap();
+        assert_eq!(altitudes(&restored), altitudes(&mission));
+    }
+
+    #[test]
+    fn test_invalid_gpx() {
+        assert!(matches!(
+            from_gpx(&b"<gpx><trk><trkseg><trkpt lat=\"48\" lon=\"11\"></trkseg></gpx>"[..]),
+            Err(LitchiError::XmlError(_))
+        ));
+        assert!(matches!(
+            from_gpx(&b"<gpx><wpt lat=\"48\"/></gpx>"[..]),
+            Err(LitchiError::InvalidWaypoint { waypoint: 0, .. })
+        ));
+    }
+}
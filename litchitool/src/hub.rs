@@ -0,0 +1,53 @@
+//! Text payload for sharing missions where a file can't be attached, like chats or links.
+//!
+//! Litchi doesn't publish a share format of its own, so the payload is simply the binary mission
+//! file (see [`LitchiMission::try_to_binary`]) encoded as standard base64. To import a shared
+//! mission in the Mission Hub, decode the payload back into a file, e.g. with
+//! `base64 -d payload.txt > shared.mission` or [`from_share_payload`], and open it via the Hub's
+//! import dialog. The mission name isn't part of the payload.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::{error::LitchiError, mission::LitchiMission};
+
+/// Encodes the mission as a base64 share payload, failing like [`LitchiMission::try_to_binary`]
+/// if it doesn't fit into the binary format
+pub fn to_share_payload(mission: &LitchiMission) -> Result<String, LitchiError> {
+    Ok(STANDARD.encode(mission.try_to_binary()?))
+}
+
+/// Decodes a mission from a share payload, ignoring surrounding whitespace
+pub fn from_share_payload(payload: &str) -> Result<LitchiMission, LitchiError> {
+    LitchiMission::from_binary(&STANDARD.decode(payload.trim())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv_format;
+
+    const TEST_MISSION_CSV: &[u8] = include_bytes!("../test/litchi_mission.csv");
+
+    #[test]
+    fn test_share_payload() {
+        let mission = csv_format::read_from_csv(csv::Reader::from_reader(TEST_MISSION_CSV))
+            .expect("Could not parse test mission from csv");
+
+        let payload = to_share_payload(&mission).expect("Could not encode payload");
+        insta::assert_snapshot!(payload);
+
+        let shared = from_share_payload(&format!("{payload}\n")).expect("Could not decode payload");
+        assert_eq!(shared, mission);
+        assert!(matches!(
+            from_share_payload("not base64!"),
+            Err(LitchiError::SharePayloadError(_))
+        ));
+
+        let mut unsupported = mission;
+        unsupported.config_mut().version = 10;
+        assert!(matches!(
+            to_share_payload(&unsupported),
+            Err(LitchiError::UnsupportedVersion { found: 10 })
+        ));
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{
+    error::LitchiError,
+    mission::{AltitudeMode, Coordinate, LitchiMission, MissionConfig, Waypoint},
+};
+
+/// Altitude in m above takeoff of imported waypoints without a usable elevation
+pub const DEFAULT_ALTITUDE: f32 = 30.;
+
+/// How elevations above sea level, like GPX `<ele>` or absolute KML altitudes, become waypoint
+/// altitudes. Missions fly relative to the takeoff point, so using them as they are would send
+/// the aircraft hundreds of meters up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ElevationMode {
+    /// Ignore the elevations and fly every waypoint at the default altitude
+    #[default]
+    Ignore,
+    /// Follow the elevation profile, flying the first point with an elevation at the default
+    /// altitude and the others as far above or below it as their elevation differs. Points
+    /// without elevation are flown at the default altitude.
+    RelativeToFirst,
+}
+
+/// Options of the [`crate::kml`] and [`crate::gpx`] importers
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// Altitude above takeoff of waypoints without a usable elevation, [`DEFAULT_ALTITUDE`] by
+    /// default
+    pub default_altitude: f32,
+    /// How elevations above sea level are used, ignored by default
+    pub elevation: ElevationMode,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            default_altitude: DEFAULT_ALTITUDE,
+            elevation: ElevationMode::default(),
+        }
+    }
+}
+
+/// Altitude of an imported point
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Elevation {
+    /// Meters above sea level, see [`ElevationMode`]
+    SeaLevel(f32),
+    /// Meters above the terrain, flown with [`AltitudeMode::AboveGround`]
+    Ground(f32),
+}
+
+/// Mission flying through the imported points, with all other settings at their defaults
+pub(crate) fn mission_from_points(
+    points: Vec<(Coordinate, Option<Elevation>)>,
+    options: &ImportOptions,
+) -> Result<LitchiMission, LitchiError> {
+    let base_elevation = match options.elevation {
+        ElevationMode::Ignore => None,
+        ElevationMode::RelativeToFirst => {
+            points.iter().find_map(|(_, elevation)| match elevation {
+                Some(Elevation::SeaLevel(elevation)) => Some(*elevation),
+                _ => None,
+            })
+        }
+    };
+
+    let waypoints = points
+        .into_iter()
+        .map(|(coordinate, elevation)| {
+            let (altitude, altitude_mode) = match (elevation, base_elevation) {
+                (Some(Elevation::Ground(altitude)), _) => (altitude, AltitudeMode::AboveGround),
+                (Some(Elevation::SeaLevel(elevation)), Some(base)) => (
+                    options.default_altitude + elevation - base,
+                    AltitudeMode::Absolute,
+                ),
+                _ => (options.default_altitude, AltitudeMode::Absolute),
+            };
+
+            Waypoint {
+                coordinate,
+                altitude,
+                altitude_mode,
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    LitchiMission::new(waypoints, vec![], MissionConfig::default())
+}
@@ -0,0 +1,445 @@
+use std::{
+    fmt::Write,
+    io::{BufReader, Read},
+};
+
+use quick_xml::{events::Event, Reader};
+
+use crate::{
+    error::LitchiError,
+    export::{waypoint_label, ExportOptions},
+    import::{mission_from_points, Elevation, ImportOptions},
+    mission::{AltitudeMode, Coordinate, LitchiMission},
+};
+
+/// Exports the mission as a KML document, for previewing missions in Google Earth: the waypoint
+/// path, a placemark per waypoint and differently styled placemarks for the POIs.
+///
+/// Altitudes above the takeoff point are written as `absolute`, which [`from_kml`] treats as
+/// above sea level and ignores by default, so importing the export again flattens them to
+/// [`ImportOptions::default_altitude`] unless [`ElevationMode::RelativeToFirst`] is used.
+///
+/// [`ElevationMode::RelativeToFirst`]: crate::import::ElevationMode::RelativeToFirst
+pub fn to_kml(mission: &LitchiMission) -> String {
+    to_kml_with_options(mission, &ExportOptions::default())
+}
+
+pub fn to_kml_with_options(mission: &LitchiMission, options: &ExportOptions) -> String {
+    let mut kml = String::new();
+
+    // Writing to a String can't fail
+    let _ = write_kml(&mut kml, mission, options);
+
+    kml
+}
+
+/// KML altitude mode of an [`AltitudeMode`]. KML has no mode relative to the takeoff point, so
+/// those altitudes are shown as absolute.
+fn kml_altitude_mode(altitude_mode: AltitudeMode) -> &'static str {
+    match altitude_mode {
+        AltitudeMode::Absolute => "absolute",
+        AltitudeMode::AboveGround => "relativeToGround",
+    }
+}
+
+fn write_kml(
+    kml: &mut String,
+    mission: &LitchiMission,
+    options: &ExportOptions,
+) -> std::fmt::Result {
+    writeln!(kml, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(kml, r#"<kml xmlns="http://www.opengis.net/kml/2.2">"#)?;
+    writeln!(kml, "<Document>")?;
+    for (id, color) in [("waypoint", "ff00aaff"), ("poi", "ff0000ff")] {
+        writeln!(kml, r#"  <Style id="{id}">"#)?;
+        writeln!(kml, "    <IconStyle><color>{color}</color></IconStyle>")?;
+        writeln!(kml, "  </Style>")?;
+    }
+
+    // A line has a single altitude mode, the first waypoint's stands in for all of them
+    let path_altitude_mode = mission
+        .waypoints()
+        .first()
+        .map_or(AltitudeMode::Absolute, |waypoint| waypoint.altitude_mode);
+    writeln!(kml, "  <Placemark>")?;
+    writeln!(kml, "    <name>Path</name>")?;
+    writeln!(kml, "    <LineString>")?;
+    writeln!(
+        kml,
+        "      <altitudeMode>{}</altitudeMode>",
+        kml_altitude_mode(path_altitude_mode)
+    )?;
+    writeln!(kml, "      <coordinates>")?;
+    for waypoint in mission.waypoints() {
+        writeln!(
+            kml,
+            "        {},{},{}",
+            waypoint.coordinate.1, waypoint.coordinate.0, waypoint.altitude
+        )?;
+    }
+    writeln!(kml, "      </coordinates>")?;
+    writeln!(kml, "    </LineString>")?;
+    writeln!(kml, "  </Placemark>")?;
+
+    for (index, (waypoint, (distance, _))) in mission
+        .waypoints()
+        .iter()
+        .zip(mission.altitude_profile())
+        .enumerate()
+    {
+        let name = if options.labels {
+            waypoint_label(index, distance)
+        } else {
+            format!("Waypoint {}", index + 1)
+        };
+        write_point(
+            kml,
+            &name,
+            "waypoint",
+            &waypoint.coordinate,
+            waypoint.altitude,
+            waypoint.altitude_mode,
+        )?;
+    }
+
+    for (index, poi) in mission.pois().iter().enumerate() {
+        write_point(
+            kml,
+            &format!("POI {}", index + 1),
+            "poi",
+            &poi.coordinate,
+            poi.altitude,
+            poi.altitude_mode,
+        )?;
+    }
+
+    writeln!(kml, "</Document>")?;
+    writeln!(kml, "</kml>")
+}
+
+fn write_point(
+    kml: &mut String,
+    name: &str,
+    style: &str,
+    coordinate: &Coordinate,
+    altitude: f32,
+    altitude_mode: AltitudeMode,
+) -> std::fmt::Result {
+    writeln!(kml, "  <Placemark>")?;
+    writeln!(kml, "    <name>{name}</name>")?;
+    writeln!(kml, "    <description>Altitude {altitude} m</description>")?;
+    writeln!(kml, "    <styleUrl>#{style}</styleUrl>")?;
+    writeln!(kml, "    <Point>")?;
+    writeln!(
+        kml,
+        "      <altitudeMode>{}</altitudeMode>",
+        kml_altitude_mode(altitude_mode)
+    )?;
+    writeln!(
+        kml,
+        "      <coordinates>{},{},{}</coordinates>",
+        coordinate.1, coordinate.0, altitude
+    )?;
+    writeln!(kml, "    </Point>")?;
+    writeln!(kml, "  </Placemark>")
+}
+
+/// Imports the paths of a KML document as waypoints, or its points if it has no paths, like the
+/// documents written by [`to_kml`]. All other settings are defaults.
+///
+/// Altitudes follow the `<altitudeMode>` of their geometry: `relativeToGround` ones are flown
+/// above ground, `absolute` ones are above sea level and only used as configured by
+/// [`ImportOptions::elevation`], and the default `clampToGround` ignores them.
+pub fn from_kml<R: Read>(reader: R) -> Result<LitchiMission, LitchiError> {
+    from_kml_with_options(reader, &ImportOptions::default())
+}
+
+pub fn from_kml_with_options<R: Read>(
+    reader: R,
+    options: &ImportOptions,
+) -> Result<LitchiMission, LitchiError> {
+    let mut reader = Reader::from_reader(BufReader::new(reader));
+    reader.config_mut().trim_text(true);
+
+    let mut path = vec![];
+    let mut points = vec![];
+    let mut geometry: Option<Geometry> = None;
+    let mut in_coordinates = false;
+    let mut in_altitude_mode = false;
+    let mut buf = vec![];
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(element) => match element.local_name().as_ref() {
+                name @ (b"LineString" | b"Point") => {
+                    geometry = Some(Geometry {
+                        is_path: name == b"LineString",
+                        tuples: vec![],
+                        altitude_mode: "clampToGround".to_string(),
+                    })
+                }
+                b"coordinates" => in_coordinates = geometry.is_some(),
+                b"altitudeMode" => in_altitude_mode = geometry.is_some(),
+                _ => {}
+            },
+            Event::End(element) => match element.local_name().as_ref() {
+                b"LineString" | b"Point" => {
+                    if let Some(geometry) = geometry.take() {
+                        let target = if geometry.is_path {
+                            &mut path
+                        } else {
+                            &mut points
+                        };
+                        let altitude_mode = geometry.altitude_mode;
+                        target.extend(geometry.tuples.into_iter().map(|(coordinate, altitude)| {
+                            (coordinate, elevation(altitude, &altitude_mode))
+                        }));
+                    }
+                }
+                b"coordinates" => in_coordinates = false,
+                b"altitudeMode" => in_altitude_mode = false,
+                _ => {}
+            },
+            Event::Text(text) if in_altitude_mode => {
+                if let Some(geometry) = &mut geometry {
+                    geometry.altitude_mode = text.unescape()?.trim().to_string();
+                }
+            }
+            Event::Text(text) if in_coordinates => {
+                if let Some(geometry) = &mut geometry {
+                    let offset = if geometry.is_path {
+                        path.len()
+                    } else {
+                        points.len()
+                    };
+                    for tuple in text.unescape()?.split_whitespace() {
+                        let index = offset + geometry.tuples.len();
+                        geometry.tuples.push(read_tuple(tuple, index)?);
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    if path.is_empty() {
+        path = points;
+    }
+    mission_from_points(path, options)
+}
+
+/// A `<LineString>` or `<Point>` being read, whose `<altitudeMode>` may follow its coordinates
+struct Geometry {
+    is_path: bool,
+    tuples: Vec<(Coordinate, Option<f32>)>,
+    altitude_mode: String,
+}
+
+/// The elevation of an altitude in the given KML altitude mode, `None` if it is clamped to the
+/// ground or sea floor
+fn elevation(altitude: Option<f32>, altitude_mode: &str) -> Option<Elevation> {
+    match altitude_mode {
+        "relativeToGround" => altitude.map(Elevation::Ground),
+        "absolute" => altitude.map(Elevation::SeaLevel),
+        _ => None,
+    }
+}
+
+/// Coordinate and altitude of a `lon,lat[,alt]` tuple, the `index`th point of its kind
+fn read_tuple(tuple: &str, index: usize) -> Result<(Coordinate, Option<f32>), LitchiError> {
+    let values: Vec<&str> = tuple.split(',').collect();
+    let (lon, lat, altitude) = match values[..] {
+        [lon, lat] => (lon, lat, None),
+        [lon, lat, altitude] => (lon, lat, Some(altitude.parse()?)),
+        _ => Err(LitchiError::InvalidWaypoint {
+            waypoint: index,
+            reason: format!("coordinates {tuple:?} are not lon,lat[,alt]"),
+        })?,
+    };
+
+    Ok((Coordinate(lat.parse()?, lon.parse()?), altitude))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{
+        csv_format,
+        import::{ElevationMode, DEFAULT_ALTITUDE},
+        mission::{MissionConfig, Waypoint, POI},
+    };
+
+    const TEST_MISSION_CSV: &[u8] = include_bytes!("../test/litchi_mission.csv");
+
+    pub(crate) fn test_mission() -> LitchiMission {
+        let waypoints = [(48.0, 11.0), (48.001, 11.0), (48.001, 11.002)]
+            .into_iter()
+            .map(|(latitude, longitude)| Waypoint {
+                coordinate: Coordinate(latitude, longitude),
+                altitude: 30.,
+                ..Default::default()
+            })
+            .collect();
+
+        LitchiMission::new(waypoints, vec![], MissionConfig::default()).unwrap()
+    }
+
+    /// Like [`test_mission`], but climbing and descending instead of flying at
+    /// [`DEFAULT_ALTITUDE`]
+    pub(crate) fn profiled_mission() -> LitchiMission {
+        let waypoints = test_mission()
+            .waypoints()
+            .iter()
+            .zip([40., 55.5, 48.])
+            .map(|(waypoint, altitude)| Waypoint {
+                altitude,
+                ..waypoint.clone()
+            })
+            .collect();
+
+        LitchiMission::new(waypoints, vec![], MissionConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_labels() {
+        let kml = to_kml_with_options(&test_mission(), &ExportOptions { labels: true });
+
+        insta::assert_snapshot!(kml);
+    }
+
+    #[test]
+    fn test_fixture() {
+        let mission = csv_format::read_from_csv(csv::Reader::from_reader(TEST_MISSION_CSV))
+            .expect("Could not parse test mission from csv");
+
+        insta::assert_snapshot!(to_kml(&mission));
+    }
+
+    #[test]
+    fn test_pois() {
+        let mission = test_mission();
+        let mut waypoints = mission.waypoints().clone();
+        waypoints[0].altitude_mode = AltitudeMode::AboveGround;
+        waypoints[0].poi_index = Some(0);
+        let mission = LitchiMission::new(
+            waypoints,
+            vec![POI {
+                coordinate: Coordinate(48.0005, 11.001),
+                altitude: 5.,
+                altitude_mode: AltitudeMode::AboveGround,
+            }],
+            MissionConfig::default(),
+        )
+        .unwrap();
+
+        insta::assert_snapshot!(to_kml(&mission));
+    }
+
+    #[test]
+    fn test_from_kml() {
+        let mission = profiled_mission();
+        let kml = to_kml(&mission);
+        let altitudes = |mission: &LitchiMission| -> Vec<_> {
+            mission.waypoints().iter().map(|w| w.altitude).collect()
+        };
+
+        // The altitudes above takeoff are exported as absolute ones, so they are lost by default
+        let imported = from_kml(kml.as_bytes()).expect("Could not import export");
+        assert_eq!(imported.waypoints().len(), mission.waypoints().len());
+        for (imported, original) in imported.waypoints().iter().zip(mission.waypoints()) {
+            assert_eq!(imported.coordinate, original.coordinate);
+        }
+        assert_eq!(altitudes(&imported), [DEFAULT_ALTITUDE; 3]);
+
+        let options = ImportOptions {
+            default_altitude: 40.,
+            elevation: ElevationMode::RelativeToFirst,
+        };
+        let restored = from_kml_with_options(kml.as_bytes(), &options).unwrap();
+        assert_eq!(altitudes(&restored), altitudes(&mission));
+
+        // Altitudes above ground keep their mode
+        let mut above_ground = mission.clone();
+        above_ground.set_altitude_mode(AltitudeMode::AboveGround);
+        let imported = from_kml(to_kml(&above_ground).as_bytes()).unwrap();
+        assert_eq!(altitudes(&imported), altitudes(&mission));
+        assert!(imported
+            .waypoints()
+            .iter()
+            .all(|waypoint| waypoint.altitude_mode == AltitudeMode::AboveGround));
+    }
+
+    #[test]
+    fn test_from_kml_altitude_modes() {
+        let kml = |altitude_mode: &str| {
+            format!(
+                r#"<kml><Placemark><LineString>
+                    <coordinates>11.5,48.1,520 11.6,48.2,530.5</coordinates>
+                    {altitude_mode}
+                </LineString></Placemark></kml>"#
+            )
+        };
+        let altitudes = |mission: LitchiMission| -> Vec<_> {
+            mission
+                .waypoints()
+                .iter()
+                .map(|waypoint| (waypoint.altitude, waypoint.altitude_mode))
+                .collect()
+        };
+
+        let clamped = from_kml(kml("").as_bytes()).unwrap();
+        assert_eq!(
+            altitudes(clamped),
+            [(DEFAULT_ALTITUDE, AltitudeMode::Absolute); 2]
+        );
+
+        let relative = kml("<altitudeMode>relativeToGround</altitudeMode>");
+        assert_eq!(
+            altitudes(from_kml(relative.as_bytes()).unwrap()),
+            [
+                (520., AltitudeMode::AboveGround),
+                (530.5, AltitudeMode::AboveGround)
+            ]
+        );
+
+        // Above sea level, so the altitudes are not flown above takeoff as they are
+        let absolute = kml("<altitudeMode>absolute</altitudeMode>");
+        assert_eq!(
+            altitudes(from_kml(absolute.as_bytes()).unwrap()),
+            [(DEFAULT_ALTITUDE, AltitudeMode::Absolute); 2]
+        );
+        let options = ImportOptions {
+            elevation: ElevationMode::RelativeToFirst,
+            ..Default::default()
+        };
+        assert_eq!(
+            altitudes(from_kml_with_options(absolute.as_bytes(), &options).unwrap()),
+            [
+                (DEFAULT_ALTITUDE, AltitudeMode::Absolute),
+                (DEFAULT_ALTITUDE + 10.5, AltitudeMode::Absolute)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_kml_points() {
+        let kml = r#"<kml><Document>
+            <Placemark><Point><coordinates>11.5,48.1</coordinates></Point></Placemark>
+            <Placemark><Point>
+                <altitudeMode>relativeToGround</altitudeMode>
+                <coordinates>11.6,48.2,12.5</coordinates>
+            </Point></Placemark>
+        </Document></kml>"#;
+
+        let mission = from_kml(kml.as_bytes()).expect("Could not import points");
+
+        assert_eq!(mission.waypoints()[0].coordinate, Coordinate(48.1, 11.5));
+        assert_eq!(mission.waypoints()[0].altitude, DEFAULT_ALTITUDE);
+        assert_eq!(mission.waypoints()[1].altitude, 12.5);
+        assert!(matches!(
+            from_kml(&b"<kml><Point><coordinates>11.5</coordinates></Point></kml>"[..]),
+            Err(LitchiError::InvalidWaypoint { waypoint: 0, .. })
+        ));
+    }
+}
@@ -1,10 +1,26 @@
+pub mod binary_format;
+pub mod convert;
 pub mod csv_format;
+pub mod diff;
 pub mod error;
+pub mod export;
+#[cfg(feature = "geojson")]
+pub mod geojson;
+pub mod gpx;
+pub mod hub;
+pub mod import;
+pub mod kml;
 pub mod mission;
+#[cfg(feature = "preview")]
+pub mod preview;
+pub mod validation;
 
 #[cfg(test)]
 mod tests {
-    use crate::csv_format;
+    use crate::{
+        csv_format,
+        mission::{AltitudeMode, Coordinate, LitchiMission, MissionConfig, Waypoint, POI},
+    };
 
     #[test]
     fn test_convert_mission() {
@@ -18,5 +34,47 @@ mod tests {
         insta::assert_debug_snapshot!(binary);
     }
 
+    #[test]
+    fn test_coordinates_round_trip() {
+        const TEST_MISSION_CSV: &[u8] = include_bytes!("../test/litchi_mission.csv");
+
+        let mission = csv_format::read_from_csv(csv::Reader::from_reader(TEST_MISSION_CSV))
+            .expect("Could not parse test mission from csv");
+        let binary = LitchiMission::from_binary(&mission.to_binary())
+            .expect("Could not parse binary of the test mission");
+        let mut csv = vec![];
+        csv_format::write_to_csv(&binary, csv::Writer::from_writer(&mut csv))
+            .expect("Could not write test mission to csv");
+        let reparsed = csv_format::read_from_csv(csv::Reader::from_reader(csv.as_slice()))
+            .expect("Could not parse written csv");
+
+        let coordinates = |mission: &LitchiMission| -> Vec<Coordinate> {
+            mission
+                .waypoints()
+                .iter()
+                .map(|waypoint| waypoint.coordinate.clone())
+                .collect()
+        };
+        assert_eq!(coordinates(&binary), coordinates(&mission));
+        assert_eq!(coordinates(&reparsed), coordinates(&mission));
+
+        let poi_mission = LitchiMission::new(
+            vec![Waypoint {
+                coordinate: Coordinate(48.137154, 11.576124),
+                poi_index: Some(0),
+                ..Default::default()
+            }],
+            vec![POI {
+                coordinate: Coordinate(48.138, 11.577),
+                altitude: 5.,
+                altitude_mode: AltitudeMode::AboveGround,
+            }],
+            MissionConfig::default(),
+        )
+        .unwrap();
+        let parsed = LitchiMission::from_binary(&poi_mission.to_binary()).unwrap();
+        assert_eq!(parsed.pois()[0].coordinate, Coordinate(48.138, 11.577));
+    }
+
     // TODO: More tests
 }
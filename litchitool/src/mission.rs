@@ -1,28 +1,126 @@
 use std::{
     f64::consts::PI,
+    fmt,
     hash::{Hash, Hasher},
+    ops::RangeInclusive,
+    time::Duration,
 };
 
-use bytes::{BufMut, Bytes, BytesMut};
+use bytes::{BufMut, Bytes};
 use num_enum::TryFromPrimitive;
+use tracing::warn;
 
-use crate::error::LitchiError;
+use crate::{
+    binary_format::{BinaryWriter, SIGNATURE},
+    error::LitchiError,
+};
+
+/// Waypoint and initial headings in degrees
+pub const HEADING_RANGE: RangeInclusive<f32> = -180.0..=180.;
+
+/// Wraps a heading in degrees into [`HEADING_RANGE`], e.g. 270° to -90°. Headings already in
+/// range, including -180° and 180°, are returned as is.
+pub fn normalize_heading(heading: f32) -> f32 {
+    if HEADING_RANGE.contains(&heading) || !heading.is_finite() {
+        return heading;
+    }
+
+    let wrapped = heading.rem_euclid(360.);
+    if wrapped > 180. {
+        wrapped - 360.
+    } else {
+        wrapped
+    }
+}
+
+/// The binary format version read and written, the only one whose layout is known
+pub const BINARY_VERSION: i16 = 11;
+
+/// Maximum number of actions Litchi stores per waypoint
+pub const MAX_ACTIONS: usize = 15;
+
+/// Headings accepted by [`Action::RotateAircraft`], covering both the -180..180 and the 0..360
+/// conventions
+pub const ROTATION_RANGE: RangeInclusive<i32> = -180..=360;
+
+/// Cruising speeds in m/s accepted by current Litchi apps, negative speeds fly the path backwards
+pub const CRUISING_SPEED_RANGE: RangeInclusive<f32> = -15.0..=15.;
+/// Maximum speeds in m/s when flying with the remote accepted by current Litchi apps
+pub const RC_SPEED_RANGE: RangeInclusive<f32> = 2.0..=15.;
+
+/// Mean earth radius in meters, used for great-circle distances
+pub const EARTH_RADIUS: f64 = 6_371_000.;
 
 /// Cardinal coordinates (latitude, longitude)
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Coordinate(pub f64, pub f64);
 
-#[derive(Debug, Clone, Copy)]
+/// Waypoint action, covering all action types of Litchi's CSV and binary format (0 to 5).
+/// Photo intervals aren't actions in Litchi but separate fields, see [`PhotoInterval`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", content = "param")
+)]
 pub enum Action {
+    /// Hover for the given number of seconds, stored as whole milliseconds
     StayFor(f32),
     TakePhoto,
     StartRecording,
     StopRecording,
+    /// Turn the aircraft to the given heading in degrees. The format has no rotation direction,
+    /// the aircraft picks one itself. Must lie within [`ROTATION_RANGE`].
     RotateAircraft(i32),
     TiltCamera(i32),
 }
 
-#[derive(Debug, Clone)]
+/// Builds the action list of a single waypoint, rejecting orderings the aircraft can't fly, like
+/// stopping a recording that was never started
+#[derive(Debug, Clone, Default)]
+pub struct ActionSequenceBuilder {
+    actions: Vec<Action>,
+    recording: bool,
+}
+
+/// Builds a [`LitchiMission`] waypoint by waypoint, using the [`Waypoint`] defaults for
+/// everything not set explicitly. Errors of the setters are reported by
+/// [`MissionBuilder::build`].
+///
+/// ```
+/// # use litchitool::mission::{Action, MissionBuilder};
+/// let mission = MissionBuilder::new()
+///     .waypoint(48.137, 11.575, 30.)
+///     .speed(5.)
+///     .action(Action::TakePhoto)
+///     .waypoint(48.138, 11.576, 40.)
+///     .gimbal_pitch(-45)
+///     .build()
+///     .unwrap();
+/// assert_eq!(mission.waypoints().len(), 2);
+/// ```
+#[derive(Debug, Default)]
+pub struct MissionBuilder {
+    waypoints: Vec<Waypoint>,
+    pois: Vec<POI>,
+    config: MissionConfig,
+    /// Whether a recording started at an earlier waypoint is still running
+    recording: bool,
+    error: Option<LitchiError>,
+}
+
+/// Sets up a waypoint of a [`MissionBuilder`], which is added once the next waypoint is started
+/// or the mission is built
+#[derive(Debug)]
+pub struct WaypointBuilder {
+    mission: MissionBuilder,
+    waypoint: Waypoint,
+    actions: ActionSequenceBuilder,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PhotoInterval {
     /// Time in seconds
     Time(f32),
@@ -30,16 +128,25 @@ pub enum PhotoInterval {
     Distance(f32),
 }
 
-#[derive(Debug, Clone, Copy, TryFromPrimitive)]
+/// How the aircraft's heading is controlled between waypoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
 pub enum HeadingMode {
+    /// Faces the direction of travel
     Auto,
+    /// Keeps the heading it had when the mission started
     Initial,
+    /// Controlled by the pilot with the remote, waypoint headings are not used
     Manual,
+    /// Turns to each waypoint's `heading`, interpolating between waypoints. Headings are written
+    /// exactly as given and not replaced by the bearing to the waypoint's POI, so they must
+    /// already lie within -180..=180.
     Custom,
 }
 
-#[derive(Debug, Clone, Copy, TryFromPrimitive)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
 pub enum FinishAction {
     None,
@@ -49,14 +156,16 @@ pub enum FinishAction {
     Reverse,
 }
 
-#[derive(Debug, Clone, Copy, TryFromPrimitive)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
 pub enum PathMode {
     StraightLines,
     CurvedTurns,
 }
 
-#[derive(Debug, Clone, Copy, TryFromPrimitive)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
 pub enum GimbalPitchMode {
     Disabled,
@@ -64,19 +173,36 @@ pub enum GimbalPitchMode {
     Interpolate,
 }
 
+/// Reference an altitude is measured against, stored in the `altitudemode` and
+/// `poi_altitudemode` CSV columns
 #[derive(Debug, Clone, Copy, Hash, PartialEq, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i16)]
 pub enum AltitudeMode {
+    /// `0`: Relative to the takeoff point ("above takeoff" in the Litchi apps)
     Absolute,
+    /// `1`: Relative to the terrain below the waypoint, resolved by Litchi from its elevation data
     AboveGround,
 }
 
-#[derive(Debug, Clone)]
+/// How [`LitchiMission::clamp_to_geofence`] handles waypoints outside the geofence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeofenceMode {
+    /// Drop the waypoints, keeping the rest of the route as is
+    Remove,
+    /// Move the waypoints onto the nearest point of the geofence boundary
+    Clamp,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Waypoint {
     pub coordinate: Coordinate,
     pub altitude: f32,
-    /// Heading of the waypoint, must be between -180 and 180 TODO: validate
+    /// Heading of the waypoint between -180 and 180, only flown with [`HeadingMode::Custom`].
+    /// [`LitchiMission::new`] wraps headings outside of that range.
     pub heading: f32,
+    /// Radius in meters of the curve flown through the waypoint with [`PathMode::CurvedTurns`]
     pub curve_size: f32,
     pub rotation_dir: i32,
     pub gimbal_mode: GimbalPitchMode,
@@ -87,19 +213,23 @@ pub struct Waypoint {
     pub actions: Vec<Action>,
     pub photo_interval: Option<PhotoInterval>,
     pub turn_mode: i32, // TODO: enum, what is this??
+    /// Time to hover at the waypoint in whole seconds, see [`Waypoint::stay_duration`]
     pub stay_time: i16,
+    /// Seconds the aircraft may take to fly the leg to the next waypoint, 0 or less for no limit
     pub max_reach_time: i16,
     pub repeat_actions: i32,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct POI {
     pub coordinate: Coordinate,
     pub altitude: f32,
     pub altitude_mode: AltitudeMode,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MissionConfig {
     pub heading_mode: HeadingMode,
     pub finish_action: FinishAction,
@@ -109,42 +239,175 @@ pub struct MissionConfig {
     pub n_repeat: i32,
     pub version: i16,
     pub photo_interval: Option<PhotoInterval>,
+    /// Heading between -180 and 180 the aircraft turns to at the start of a
+    /// [`HeadingMode::Initial`] mission, instead of keeping its takeoff heading. The binary format
+    /// has no field for it, so it is written as the heading of the first waypoint, and read
+    /// missions leave it `None` with the heading kept on that waypoint.
+    pub initial_heading: Option<f32>,
+}
+
+/// Default [`MissionConstraints::max_curve_size`] in meters
+pub const MAX_CURVE_SIZE: f32 = 1000.;
+
+/// Limits of the Litchi app a mission is written for. Speeds outside of them are clamped with a
+/// warning when converting to the binary format, and values outside of any limit are reported by
+/// [`LitchiMission::validation_notes`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MissionConstraints {
+    pub cruising_speed: RangeInclusive<f32>,
+    pub rc_speed: RangeInclusive<f32>,
+    /// Largest plausible curve size in meters, larger ones were likely entered in another unit
+    pub max_curve_size: f32,
+}
+
+/// Power consumption of an aircraft, used by [`LitchiMission::estimated_battery_percent`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DroneProfile {
+    /// Energy used per km flown at cruising speed in Wh
+    pub cruise_draw_wh_per_km: f32,
+    /// Power drawn while hovering in W
+    pub hover_draw_w: f32,
+    /// Usable battery capacity in Wh
+    pub battery_wh: f32,
+}
+
+impl DroneProfile {
+    /// DJI Mavic 3, 77 Wh battery with about 40 minutes of hover time
+    pub const MAVIC_3: Self = Self {
+        cruise_draw_wh_per_km: 2.8,
+        hover_draw_w: 115.,
+        battery_wh: 77.,
+    };
+    /// DJI Mini 3 Pro with the standard 18 Wh battery
+    pub const MINI_3_PRO: Self = Self {
+        cruise_draw_wh_per_km: 0.9,
+        hover_draw_w: 32.,
+        battery_wh: 18.1,
+    };
+    /// DJI Phantom 4 Pro, 89 Wh battery with about 28 minutes of hover time
+    pub const PHANTOM_4_PRO: Self = Self {
+        cruise_draw_wh_per_km: 5.,
+        hover_draw_w: 190.,
+        battery_wh: 89.2,
+    };
 }
 
-#[derive(Debug, Default, Clone)]
+/// With the `serde` feature the mission can be (de)serialized, deserialization checks it with
+/// [`LitchiMission::validate`]
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct LitchiMission {
     waypoints: Vec<Waypoint>,
     pois: Vec<POI>,
     config: MissionConfig,
+    constraints: MissionConstraints,
 }
 
 impl LitchiMission {
+    /// Creates and validates the mission. Waypoint headings outside of -180..=180 are wrapped
+    /// into it with a warning, see [`normalize_heading`].
     pub fn new(
-        waypoints: Vec<Waypoint>,
+        mut waypoints: Vec<Waypoint>,
         pois: Vec<POI>,
         config: MissionConfig,
     ) -> Result<Self, LitchiError> {
+        for (index, waypoint) in waypoints.iter_mut().enumerate() {
+            let heading = normalize_heading(waypoint.heading);
+            if heading != waypoint.heading && heading.is_finite() {
+                warn!(
+                    waypoint = index,
+                    heading = waypoint.heading,
+                    normalized = heading,
+                    "Wrapping waypoint heading into -180..=180"
+                );
+                waypoint.heading = heading;
+            }
+        }
+
         let new = Self {
             waypoints,
             pois,
             config,
+            constraints: MissionConstraints::default(),
         };
 
-        if new.validate() {
-            Ok(new)
-        } else {
-            Err(LitchiError::InvalidMission)
-        }
+        new.validate()?;
+        Ok(new)
     }
 
-    fn validate(&self) -> bool {
-        // TODO: Check coordinates, heights, speeds, angles, etc.
+    /// Checks that the mission is valid, like [`LitchiMission::new`] does. Useful after editing
+    /// the mission through [`LitchiMission::config_mut`].
+    ///
+    /// Fails for constraints that aren't valid, the first waypoint with coordinates off the
+    /// globe, a negative altitude above ground, a speed outside of the cruising speed constraints,
+    /// a POI index without POI, an action that can't be encoded, or (with [`HeadingMode::Custom`],
+    /// the only mode flying the waypoint headings) a heading outside of -180..=180. A version
+    /// other than [`BINARY_VERSION`] fails with [`LitchiError::UnsupportedVersion`], as it can't
+    /// be written.
+    pub fn validate(&self) -> Result<(), LitchiError> {
+        self.constraints.validate()?;
 
-        self.waypoints.iter().all(|waypoint| {
-            waypoint
-                .poi_index
-                .map_or(true, |index| index < self.pois.len())
-        })
+        if self.config.version != BINARY_VERSION {
+            Err(LitchiError::UnsupportedVersion {
+                found: self.config.version,
+            })?;
+        }
+
+        if let Some(heading) = self.config.initial_heading {
+            if !HEADING_RANGE.contains(&heading) {
+                Err(LitchiError::InvalidMission(format!(
+                    "initial heading {heading}° is outside of -180..=180"
+                )))?;
+            }
+        }
+
+        let custom_heading = self.config.heading_mode == HeadingMode::Custom;
+        let speed_range = &self.constraints.cruising_speed;
+        for (index, waypoint) in self.waypoints.iter().enumerate() {
+            let invalid = |reason: String| {
+                Err(LitchiError::InvalidWaypoint {
+                    waypoint: index,
+                    reason,
+                })
+            };
+
+            if !waypoint.coordinate.valid() {
+                invalid(format!(
+                    "coordinate {} is off the globe",
+                    waypoint.coordinate
+                ))?;
+            }
+            if custom_heading && !HEADING_RANGE.contains(&waypoint.heading) {
+                invalid(format!(
+                    "heading {}° is outside of -180..=180",
+                    waypoint.heading
+                ))?;
+            }
+            if waypoint.altitude_mode == AltitudeMode::AboveGround && waypoint.altitude < 0. {
+                invalid(format!(
+                    "altitude {} m is below the ground",
+                    waypoint.altitude
+                ))?;
+            }
+            // Negative speeds are allowed like for the cruising speed, 0 uses the cruising speed
+            if waypoint.speed != 0. && !speed_range.contains(&waypoint.speed) {
+                invalid(format!(
+                    "speed {} m/s is outside of {}..={}",
+                    waypoint.speed,
+                    speed_range.start(),
+                    speed_range.end()
+                ))?;
+            }
+            if let Some(poi) = waypoint.poi_index.filter(|poi| *poi >= self.pois.len()) {
+                invalid(format!("POI {poi} does not exist"))?;
+            }
+            for action in &waypoint.actions {
+                action.idx_and_param()?;
+            }
+        }
+
+        Ok(())
     }
 
     pub fn pois(&self) -> &Vec<POI> {
@@ -155,6 +418,24 @@ impl LitchiMission {
         &self.waypoints
     }
 
+    /// The waypoints alongside the POI they reference, if any. A POI index without POI, which
+    /// [`LitchiMission::validate`] rejects, is logged and yields `None` as well.
+    pub fn waypoints_with_pois(&self) -> impl Iterator<Item = (&Waypoint, Option<&POI>)> {
+        self.waypoints.iter().enumerate().map(|(index, waypoint)| {
+            let poi = waypoint.poi_index.and_then(|poi_index| {
+                let poi = self.pois.get(poi_index);
+                if poi.is_none() {
+                    warn!(
+                        waypoint = index,
+                        poi_index, "Waypoint references a missing POI"
+                    );
+                }
+                poi
+            });
+            (waypoint, poi)
+        })
+    }
+
     pub fn config(&self) -> &MissionConfig {
         &self.config
     }
@@ -163,43 +444,676 @@ impl LitchiMission {
         &mut self.config
     }
 
+    pub fn constraints(&self) -> &MissionConstraints {
+        &self.constraints
+    }
+
+    /// Targets an app with other limits than [`MissionConstraints::default`], for chaining. Fails
+    /// for constraints that aren't valid, see [`MissionConstraints::validate`].
+    pub fn with_constraints(
+        mut self,
+        constraints: MissionConstraints,
+    ) -> Result<Self, LitchiError> {
+        constraints.validate()?;
+        self.constraints = constraints;
+        Ok(self)
+    }
+
+    /// Replaces the config, for chaining. Like with [`LitchiMission::config_mut`], the mission is
+    /// not validated again.
+    ///
+    /// ```
+    /// use litchitool::{
+    ///     csv_format,
+    ///     mission::{FinishAction, MissionConfig},
+    /// };
+    ///
+    /// # let csv = include_bytes!("../test/litchi_mission.csv").as_slice();
+    /// let binary = csv_format::read_from_csv(csv::Reader::from_reader(csv))?
+    ///     .with_config(MissionConfig {
+    ///         cruising_speed: 5.,
+    ///         ..Default::default()
+    ///     })
+    ///     .map_config(|config| config.finish_action = FinishAction::Land)
+    ///     .to_binary();
+    /// # Ok::<(), litchitool::error::LitchiError>(())
+    /// ```
+    pub fn with_config(mut self, config: MissionConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Edits the config in place, for chaining, see [`LitchiMission::with_config`]
+    pub fn map_config(mut self, f: impl FnOnce(&mut MissionConfig)) -> Self {
+        f(&mut self.config);
+        self
+    }
+
+    /// Appends the waypoints in reverse order (without repeating the last one), turning a one-way
+    /// route into an out-and-back. The appended waypoints keep their POI but get their heading
+    /// recomputed to face their POI, or the direction they are approached from otherwise.
+    pub fn append_return_path(&mut self) {
+        let return_path: Vec<Waypoint> = self.waypoints.iter().rev().skip(1).cloned().collect();
+
+        for mut waypoint in return_path {
+            let previous = self
+                .waypoints
+                .last()
+                .expect("Return path is only non-empty when there are waypoints");
+
+            waypoint.heading = match waypoint.poi_index.and_then(|index| self.pois.get(index)) {
+                Some(poi) => waypoint.coordinate.heading_towards(&poi.coordinate),
+                None => previous.coordinate.heading_towards(&waypoint.coordinate),
+            } as f32;
+
+            self.waypoints.push(waypoint);
+        }
+    }
+
+    /// Appends the waypoints of `other`, to build a mission from segments. Its POIs are merged
+    /// into this mission's, reusing equal POIs, and its waypoints' POI indices remapped. The
+    /// config of this mission is kept. Fails, leaving the mission unchanged, if the combined
+    /// mission isn't valid under this mission's constraints.
+    pub fn append(&mut self, other: &LitchiMission) -> Result<(), LitchiError> {
+        let mut pois = self.pois.clone();
+        let poi_indices: Vec<usize> = other
+            .pois
+            .iter()
+            .map(|poi| {
+                pois.iter()
+                    .position(|known| known == poi)
+                    .unwrap_or_else(|| {
+                        pois.push(poi.clone());
+                        pois.len() - 1
+                    })
+            })
+            .collect();
+
+        let mut waypoints = self.waypoints.clone();
+        waypoints.extend(other.waypoints.iter().map(|waypoint| Waypoint {
+            poi_index: waypoint.poi_index.map(|index| poi_indices[index]),
+            ..waypoint.clone()
+        }));
+
+        let combined = Self {
+            waypoints,
+            pois,
+            config: self.config.clone(),
+            constraints: self.constraints.clone(),
+        };
+        combined.validate()?;
+        *self = combined;
+        Ok(())
+    }
+
+    /// Reverses the order of the waypoints, to fly the mission backwards. Waypoints keep their
+    /// actions and POI. With `recompute_headings`, each heading is recomputed like in
+    /// [`LitchiMission::append_return_path`]: facing the waypoint's POI, or the direction it is
+    /// approached from otherwise (towards the next waypoint for the first one).
+    pub fn reverse(&mut self, recompute_headings: bool) {
+        self.waypoints.reverse();
+        if !recompute_headings {
+            return;
+        }
+
+        let headings: Vec<f32> = (0..self.waypoints.len())
+            .map(|index| {
+                let waypoint = &self.waypoints[index];
+                let poi = waypoint.poi_index.and_then(|index| self.pois.get(index));
+                let heading = match (poi, index.checked_sub(1)) {
+                    (Some(poi), _) => waypoint.coordinate.heading_towards(&poi.coordinate),
+                    (None, Some(previous)) => self.waypoints[previous]
+                        .coordinate
+                        .heading_towards(&waypoint.coordinate),
+                    (None, None) => match self.waypoints.get(1) {
+                        Some(next) => waypoint.coordinate.heading_towards(&next.coordinate),
+                        None => waypoint.heading.into(),
+                    },
+                };
+                heading as f32
+            })
+            .collect();
+
+        for (waypoint, heading) in self.waypoints.iter_mut().zip(headings) {
+            waypoint.heading = heading;
+        }
+    }
+
+    /// Puts back headings saved before importing, one per waypoint, undoing headings that were
+    /// overridden to face a POI. With [`HeadingMode::Custom`] the headings must be between -180
+    /// and 180; the mission is left unchanged if they don't fit.
+    pub fn restore_headings(&mut self, headings: &[f32]) -> Result<(), LitchiError> {
+        if headings.len() != self.waypoints.len() {
+            Err(LitchiError::HeadingCountMismatch(
+                headings.len(),
+                self.waypoints.len(),
+            ))?;
+        }
+        if self.config.heading_mode == HeadingMode::Custom {
+            if let Some(waypoint) = headings
+                .iter()
+                .position(|heading| !HEADING_RANGE.contains(heading))
+            {
+                Err(LitchiError::InvalidWaypoint {
+                    waypoint,
+                    reason: format!("heading {}° is outside of -180..=180", headings[waypoint]),
+                })?;
+            }
+        }
+
+        for (waypoint, heading) in self.waypoints.iter_mut().zip(headings) {
+            waypoint.heading = *heading;
+        }
+        Ok(())
+    }
+
+    /// Measures the altitudes of all waypoints against `altitude_mode`. The altitudes themselves
+    /// are kept, not converted.
+    pub fn set_altitude_mode(&mut self, altitude_mode: AltitudeMode) {
+        for waypoint in &mut self.waypoints {
+            waypoint.altitude_mode = altitude_mode;
+        }
+    }
+
+    /// Removes all POIs, unlinking the waypoints from them. Waypoints focusing a POI get their
+    /// gimbal disabled, while their heading is left as is.
+    pub fn strip_pois(&mut self) {
+        self.pois.clear();
+
+        for waypoint in &mut self.waypoints {
+            waypoint.poi_index = None;
+            if let GimbalPitchMode::FocusPOI = waypoint.gimbal_mode {
+                waypoint.gimbal_mode = GimbalPitchMode::Disabled;
+            }
+        }
+    }
+
+    /// Brings all waypoints into the geofence given by its (south west, north east) corners,
+    /// returning how many waypoints were outside.
+    ///
+    /// Removing waypoints keeps the remaining ones exactly where they were but changes the route,
+    /// while clamping keeps every waypoint and its actions but moves them. POIs only serve as
+    /// camera targets and may stay outside the geofence, unless no waypoint references them
+    /// anymore, in which case they are dropped.
+    ///
+    /// Fails with [`LitchiError::InvalidGeofence`], leaving the mission as is, if a corner is off
+    /// the globe or the south west corner lies north or east of the north east one.
+    pub fn clamp_to_geofence(
+        &mut self,
+        bbox: (Coordinate, Coordinate),
+        mode: GeofenceMode,
+    ) -> Result<usize, LitchiError> {
+        let (south_west, north_east) = bbox;
+        if !south_west.valid() || !north_east.valid() {
+            Err(LitchiError::InvalidGeofence(format!(
+                "corners {south_west} and {north_east} must be on the globe"
+            )))?;
+        }
+        if south_west.0 > north_east.0 || south_west.1 > north_east.1 {
+            Err(LitchiError::InvalidGeofence(format!(
+                "south west corner {south_west} lies north or east of {north_east}"
+            )))?;
+        }
+        let inside = |coordinate: &Coordinate| {
+            (south_west.0..=north_east.0).contains(&coordinate.0)
+                && (south_west.1..=north_east.1).contains(&coordinate.1)
+        };
+
+        let outside = self
+            .waypoints
+            .iter()
+            .filter(|waypoint| !inside(&waypoint.coordinate))
+            .count();
+
+        match mode {
+            GeofenceMode::Remove => {
+                self.waypoints
+                    .retain(|waypoint| inside(&waypoint.coordinate));
+                self.prune_pois();
+            }
+            GeofenceMode::Clamp => {
+                for waypoint in &mut self.waypoints {
+                    waypoint.coordinate = Coordinate(
+                        waypoint.coordinate.0.clamp(south_west.0, north_east.0),
+                        waypoint.coordinate.1.clamp(south_west.1, north_east.1),
+                    );
+                }
+            }
+        }
+
+        self.validate()?;
+        Ok(outside)
+    }
+
+    /// Removes waypoints that lie within `tolerance_meters` of the path between the waypoints
+    /// kept around them, using the Ramer–Douglas–Peucker algorithm, and returns how many were
+    /// removed. For dense tracks, like imported from GPX.
+    ///
+    /// The first and last waypoint and all waypoints with actions are kept. Only the coordinates
+    /// are compared, not the altitudes. POIs are kept as they are, so the POI indices of the
+    /// remaining waypoints stay valid.
+    pub fn simplify(&mut self, tolerance_meters: f64) -> usize {
+        let count = self.waypoints.len();
+        let mut keep = vec![false; count];
+        let anchors: Vec<usize> = (0..count)
+            .filter(|&index| {
+                index == 0 || index == count - 1 || !self.waypoints[index].actions.is_empty()
+            })
+            .collect();
+
+        for &anchor in &anchors {
+            keep[anchor] = true;
+        }
+        for pair in anchors.windows(2) {
+            self.keep_deviating(pair[0], pair[1], tolerance_meters, &mut keep);
+        }
+
+        let mut keep = keep.into_iter();
+        self.waypoints
+            .retain(|_| keep.next().expect("One flag per waypoint"));
+        count - self.waypoints.len()
+    }
+
+    /// Marks the waypoints between `start` and `end` that deviate more than the tolerance from
+    /// the simplified path
+    fn keep_deviating(&self, start: usize, end: usize, tolerance: f64, keep: &mut [bool]) {
+        let (start_coordinate, end_coordinate) = (
+            &self.waypoints[start].coordinate,
+            &self.waypoints[end].coordinate,
+        );
+        let farthest = (start + 1..end)
+            .map(|index| {
+                let coordinate = &self.waypoints[index].coordinate;
+                (
+                    index,
+                    coordinate.distance_to_leg(start_coordinate, end_coordinate),
+                )
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        if let Some((index, distance)) = farthest {
+            if distance > tolerance {
+                keep[index] = true;
+                self.keep_deviating(start, index, tolerance, keep);
+                self.keep_deviating(index, end, tolerance, keep);
+            }
+        }
+    }
+
+    /// Moves the mission by the given distances in meters, see [`LitchiMission::center_on`].
+    /// Negative distances move south and west.
+    pub fn shift(&mut self, north: f64, east: f64) {
+        if let Some(centroid) = self.centroid() {
+            self.center_on(centroid.offset(north, east));
+        }
+    }
+
+    /// South west and north east corner of the smallest box around all waypoints and POIs, for
+    /// zooming a map onto the mission. `None` for a mission without waypoints and POIs.
+    ///
+    /// The corners are the plain minimum and maximum latitude and longitude, so a mission crossing
+    /// the antimeridian gets a box spanning the whole globe the other way around.
+    pub fn bounding_box(&self) -> Option<(Coordinate, Coordinate)> {
+        let mut coordinates = self
+            .waypoints
+            .iter()
+            .map(|waypoint| &waypoint.coordinate)
+            .chain(self.pois.iter().map(|poi| &poi.coordinate));
+
+        let first = coordinates.next()?;
+        Some(coordinates.fold(
+            (first.clone(), first.clone()),
+            |(south_west, north_east), coordinate| {
+                (
+                    Coordinate(
+                        south_west.0.min(coordinate.0),
+                        south_west.1.min(coordinate.1),
+                    ),
+                    Coordinate(
+                        north_east.0.max(coordinate.0),
+                        north_east.1.max(coordinate.1),
+                    ),
+                )
+            },
+        ))
+    }
+
+    /// Mean position of the waypoints, `None` for a mission without waypoints. POIs don't count,
+    /// they are only camera targets. Like [`LitchiMission::bounding_box`] it doesn't handle the
+    /// antimeridian.
+    pub fn centroid(&self) -> Option<Coordinate> {
+        if self.waypoints.is_empty() {
+            return None;
+        }
+
+        let n = self.waypoints.len() as f64;
+        let (latitude, longitude) =
+            self.waypoints
+                .iter()
+                .fold((0., 0.), |(latitude, longitude), waypoint| {
+                    (
+                        latitude + waypoint.coordinate.0,
+                        longitude + waypoint.coordinate.1,
+                    )
+                });
+
+        Some(Coordinate(latitude / n, longitude / n))
+    }
+
+    /// Makes the aircraft hover at the waypoint for the given duration, by appending a
+    /// [`Action::StayFor`] to its actions
+    pub fn insert_stay(
+        &mut self,
+        waypoint_index: usize,
+        duration: Duration,
+    ) -> Result<(), LitchiError> {
+        let waypoint = self
+            .waypoints
+            .get_mut(waypoint_index)
+            .ok_or(LitchiError::InvalidWaypointIndex(waypoint_index))?;
+        if waypoint.actions.len() >= MAX_ACTIONS {
+            Err(LitchiError::InvalidActionSequence(format!(
+                "a waypoint can hold at most {MAX_ACTIONS} actions"
+            )))?;
+        }
+
+        waypoint.actions.push(Action::stay_for(duration)?);
+        Ok(())
+    }
+
+    /// Circle covering all waypoints as its center and radius in meters, `None` for a mission
+    /// without waypoints.
+    ///
+    /// The center is the [`LitchiMission::centroid`] and the radius the distance to the farthest
+    /// waypoint, which is simple but can be up to twice as large as the minimum bounding circle
+    /// for lopsided missions.
+    pub fn bounding_circle(&self) -> Option<(Coordinate, f64)> {
+        let center = self.centroid()?;
+        let radius = self
+            .waypoints
+            .iter()
+            .map(|waypoint| center.distance_to(&waypoint.coordinate))
+            .fold(0., f64::max);
+
+        Some((center, radius))
+    }
+
+    /// Moves the mission so that its centroid ends up at `new_center`. Waypoints and POIs keep
+    /// their distance and direction from the centroid, as well as their altitudes and headings.
+    /// Does nothing for a mission without waypoints.
+    pub fn center_on(&mut self, new_center: Coordinate) {
+        let Some(centroid) = self.centroid() else {
+            return;
+        };
+
+        let coordinates = self
+            .waypoints
+            .iter_mut()
+            .map(|waypoint| &mut waypoint.coordinate)
+            .chain(self.pois.iter_mut().map(|poi| &mut poi.coordinate));
+        for coordinate in coordinates {
+            let (north, east) = coordinate.offset_from(&centroid);
+            *coordinate = new_center.offset(north, east);
+        }
+    }
+
+    /// Drops POIs no waypoint references, remapping the remaining indices
+    fn prune_pois(&mut self) {
+        let mut new_indices = vec![None; self.pois.len()];
+        let mut pois = vec![];
+
+        for waypoint in &mut self.waypoints {
+            if let Some(index) = waypoint.poi_index {
+                let new_index = *new_indices[index].get_or_insert_with(|| {
+                    pois.push(self.pois[index].clone());
+                    pois.len() - 1
+                });
+                waypoint.poi_index = Some(new_index);
+            }
+        }
+
+        self.pois = pois;
+    }
+
+    /// Distance along the path from the first waypoint in meters, and altitude of every
+    /// waypoint. Altitudes are relative to each waypoint's own [`AltitudeMode`].
+    pub fn altitude_profile(&self) -> Vec<(f64, f32)> {
+        let mut distance = 0.;
+
+        self.waypoints
+            .iter()
+            .enumerate()
+            .map(|(index, waypoint)| {
+                if let Some(previous) = index.checked_sub(1).map(|index| &self.waypoints[index]) {
+                    distance += previous.coordinate.distance_to(&waypoint.coordinate);
+                }
+                (distance, waypoint.altitude)
+            })
+            .collect()
+    }
+
+    /// Legs flown between waypoints as their start and end, including the way back of
+    /// [`FinishAction::BackToFirst`] and [`FinishAction::Reverse`]
+    fn legs(&self) -> Vec<(&Waypoint, &Waypoint)> {
+        let mut legs: Vec<_> = self
+            .waypoints
+            .windows(2)
+            .map(|leg| (&leg[0], &leg[1]))
+            .collect();
+
+        match self.config.finish_action {
+            FinishAction::BackToFirst if self.waypoints.len() > 1 => {
+                legs.push((
+                    &self.waypoints[self.waypoints.len() - 1],
+                    &self.waypoints[0],
+                ));
+            }
+            FinishAction::Reverse => {
+                let way_back: Vec<_> = legs.iter().rev().map(|(from, to)| (*to, *from)).collect();
+                legs.extend(way_back);
+            }
+            _ => {}
+        }
+
+        legs
+    }
+
+    /// Length of the path through all waypoints in meters, flying straight between them. With
+    /// [`FinishAction::BackToFirst`] the loop is closed, with [`FinishAction::Reverse`] the path
+    /// is flown twice.
+    pub fn total_distance(&self) -> f64 {
+        self.legs()
+            .iter()
+            .map(|(from, to)| from.coordinate.distance_to(&to.coordinate))
+            .sum()
+    }
+
+    /// Estimated flight time along [`LitchiMission::total_distance`], flying each leg at the
+    /// speed of the waypoint it starts from, plus the stays at the waypoints. Accelerating, curves
+    /// and the flight to the first waypoint aren't accounted for, and stays are only counted once
+    /// when the path is flown in reverse. Durations too long for [`Duration`], like legs flown at
+    /// a minuscule speed, saturate at [`Duration::MAX`].
+    pub fn estimated_duration(&self) -> Duration {
+        let flight_seconds: f64 = self
+            .legs()
+            .iter()
+            .filter_map(|(from, to)| {
+                let speed = self.leg_speed(from).abs() as f64;
+                (speed > 0.).then(|| from.coordinate.distance_to(&to.coordinate) / speed)
+            })
+            .sum();
+
+        Duration::try_from_secs_f64(flight_seconds)
+            .unwrap_or(Duration::MAX)
+            .saturating_add(self.hover_duration())
+    }
+
+    /// Time spent hovering at waypoints, by their stay times and [`Action::StayFor`] actions
+    fn hover_duration(&self) -> Duration {
+        self.waypoints
+            .iter()
+            .map(|waypoint| {
+                waypoint.stay_duration()
+                    + waypoint
+                        .actions
+                        .iter()
+                        .filter_map(Action::stay_duration)
+                        .sum::<Duration>()
+            })
+            .sum()
+    }
+
+    /// Rough estimate of the battery used by the mission in percent of the capacity of `drone`.
+    ///
+    /// This is a heuristic: the whole path is assumed to be flown at cruise draw and all stays
+    /// at hover draw. Climbing, wind, curves, temperature, battery age and the flight to and from
+    /// the first and last waypoint aren't accounted for, so keep a generous reserve. Values above
+    /// 100 mean the mission can't be flown on a single battery.
+    pub fn estimated_battery_percent(&self, drone: DroneProfile) -> f32 {
+        let cruise_wh = self.total_distance() as f32 / 1000. * drone.cruise_draw_wh_per_km;
+        let hover_wh = self.hover_duration().as_secs_f32() / 3600. * drone.hover_draw_w;
+
+        (cruise_wh + hover_wh) / drone.battery_wh * 100.
+    }
+
+    /// Estimated number of photos triggered on each leg, by the `TakePhoto` actions of a waypoint
+    /// and the photo interval on the way to the next one. The last entry only counts the actions
+    /// of the last waypoint.
+    pub fn photos_per_leg(&self) -> Vec<usize> {
+        self.waypoints
+            .iter()
+            .enumerate()
+            .map(|(index, waypoint)| {
+                let action_photos = waypoint
+                    .actions
+                    .iter()
+                    .filter(|action| matches!(action, Action::TakePhoto))
+                    .count();
+
+                let interval_photos = self.waypoints.get(index + 1).map_or(0, |next| {
+                    let distance = waypoint.coordinate.distance_to(&next.coordinate);
+
+                    match waypoint
+                        .photo_interval
+                        .as_ref()
+                        .or(self.config.photo_interval.as_ref())
+                    {
+                        Some(PhotoInterval::Distance(interval)) if *interval > 0. => {
+                            (distance / *interval as f64) as usize
+                        }
+                        Some(PhotoInterval::Time(interval)) if *interval > 0. => {
+                            // Negative speeds fly backwards, at 0 the leg is never flown
+                            let speed = self.leg_speed(waypoint).abs() as f64;
+                            if speed > 0. {
+                                (distance / speed / *interval as f64) as usize
+                            } else {
+                                0
+                            }
+                        }
+                        _ => 0,
+                    }
+                });
+
+                action_photos + interval_photos
+            })
+            .collect()
+    }
+
+    /// Estimated number of photos taken during the whole mission, see
+    /// [`LitchiMission::photos_per_leg`]
+    pub fn photo_count_estimate(&self) -> usize {
+        self.photos_per_leg().iter().sum()
+    }
+
+    /// Speed flown from the waypoint, a speed of 0 uses the cruising speed
+    pub(crate) fn leg_speed(&self, waypoint: &Waypoint) -> f32 {
+        if waypoint.speed > 0. {
+            waypoint.speed
+        } else {
+            self.config.cruising_speed
+        }
+    }
+
     /// Converts the mission to the litchi binary mission format
+    ///
+    /// Panics if the mission doesn't fit into the format, see [`LitchiMission::try_to_binary`]
     pub fn to_binary(&self) -> Bytes {
-        // TODO: Calculate final size and use BytesMut::with_capacity(capacity);
-        let mut buf = BytesMut::new();
+        self.try_to_binary()
+            .expect("Mission must fit into the binary format")
+    }
 
+    /// Converts the mission to the litchi binary mission format, failing if it declares a version
+    /// other than [`BINARY_VERSION`], has more waypoints, POIs or waypoint actions than the format
+    /// can count or a POI index it can't store
+    pub fn try_to_binary(&self) -> Result<Bytes, LitchiError> {
+        let capacity = self.binary_size();
+        let mut writer = BinaryWriter::with_capacity(capacity);
+        self.encode(&mut writer)?;
+        debug_assert_eq!(writer.len(), capacity, "binary_size is out of date");
+        Ok(writer.finish())
+    }
+
+    /// Writes the binary mission format, labelling each section for annotated dumps
+    pub(crate) fn encode(&self, buf: &mut BinaryWriter) -> Result<(), LitchiError> {
         // Litchi file format signature
-        buf.put_i32(1818454125);
+        buf.label(|| "signature".to_string());
+        buf.put_i32(SIGNATURE);
         // Heading mode
+        buf.label(|| "heading mode".to_string());
         buf.put_i32(self.config.heading_mode as i32);
         // Finish action
+        buf.label(|| "finish action".to_string());
         buf.put_i32(self.config.finish_action as i32);
         // Path mode
+        buf.label(|| "path mode".to_string());
         buf.put_i32(self.config.path_mode as i32);
-        // Cruising speed (must be clamped at -15 and 15 for some reason)
-        buf.put_f32(self.config.cruising_speed.clamp(-15., 15.));
-        // Rc speed (clamed at 2..15)
-        buf.put_f32(self.config.rc_speed.clamp(2., 15.));
+        // Cruising speed, clamped to what the app accepts
+        buf.label(|| "cruising speed".to_string());
+        buf.put_f32(clamp_speed(
+            "cruising speed",
+            self.config.cruising_speed,
+            &self.constraints.cruising_speed,
+        ));
+        // Rc speed, clamped as well
+        buf.label(|| "rc speed".to_string());
+        buf.put_f32(clamp_speed(
+            "rc speed",
+            self.config.rc_speed,
+            &self.constraints.rc_speed,
+        ));
         // Number of repetitions
+        buf.label(|| "number of repetitions".to_string());
         buf.put_i32(self.config.n_repeat);
-        // Set version (hardcoded at 11)
-        buf.put_i16(11);
+        // Version, the writer only knows the layout of one
+        if self.config.version != BINARY_VERSION {
+            Err(LitchiError::UnsupportedVersion {
+                found: self.config.version,
+            })?;
+        }
+        buf.label(|| "version".to_string());
+        buf.put_i16(self.config.version);
         // (padding)
+        buf.label(|| "padding".to_string());
         buf.put_slice(&[0u8; 10]);
 
         // Number of waypoints
-        buf.put_i32(
-            self.waypoints
-                .len()
-                .try_into()
-                .expect("Number of waypoints must fit in i32"),
-        );
+        buf.label(|| "number of waypoints".to_string());
+        buf.put_i32(encode_count(
+            self.waypoints.len(),
+            LitchiError::TooManyWaypoints,
+        )?);
 
         // Waypoint information
-        for waypoint in &self.waypoints {
+        for (index, waypoint) in self.waypoints.iter().enumerate() {
+            buf.label(|| format!("waypoint {index}"));
             buf.put_f32(waypoint.altitude);
             buf.put_i32(waypoint.turn_mode);
-            buf.put_f32(waypoint.heading);
+            buf.put_f32(match self.config.initial_heading {
+                Some(heading) if index == 0 && self.config.heading_mode == HeadingMode::Initial => {
+                    heading
+                }
+                _ => waypoint.heading,
+            });
             buf.put_f32(waypoint.speed);
             buf.put_i16(waypoint.stay_time);
             buf.put_i16(waypoint.max_reach_time);
@@ -208,61 +1122,75 @@ impl LitchiMission {
             buf.put_f32(waypoint.curve_size);
             buf.put_i32(waypoint.gimbal_mode as i32);
             buf.put_i32(waypoint.gimbal_pitch_angle);
-            buf.put_i32(
-                waypoint
-                    .actions
-                    .len()
-                    .try_into()
-                    .expect("Number of waypoint actions must fit in i32"),
-            );
+            buf.put_i32(encode_count(waypoint.actions.len(), |count| {
+                LitchiError::TooManyActions {
+                    waypoint: index,
+                    count,
+                }
+            })?);
             buf.put_i32(waypoint.repeat_actions);
 
+            if !waypoint.actions.is_empty() {
+                buf.label(|| format!("waypoint {index} actions"));
+            }
             for action in &waypoint.actions {
-                let (action_n, param) = action.idx_and_param();
+                let (action_n, param) = action.idx_and_param()?;
                 buf.put_i32(action_n);
                 buf.put_i32(param);
             }
         }
 
         // Number of POI's
-        buf.put_i32(
-            self.pois
-                .len()
-                .try_into()
-                .expect("Number of POI's must fit in i32"),
-        );
+        buf.label(|| "number of POIs".to_string());
+        buf.put_i32(encode_count(self.pois.len(), LitchiError::TooManyPois)?);
 
         // POI positions
-        for poi in &self.pois {
+        for (index, poi) in self.pois.iter().enumerate() {
+            buf.label(|| format!("POI {index} position"));
             buf.put_f64(poi.coordinate.0);
             buf.put_f64(poi.coordinate.1);
             buf.put_f32(poi.altitude);
         }
 
-        // Set waypoint altitude and POI info
-        for waypoint in &self.waypoints {
+        // Set waypoint altitude and POI info. Litchi reads the altitude here as well as in the
+        // waypoint block, so both are written from the same field and can't disagree. The format
+        // has no separate absolute altitude: with `AltitudeMode::AboveGround` the altitude is
+        // relative to the terrain, which Litchi resolves from its own elevation data, so only an
+        // altitude below the ground is invalid (rejected by `validate`).
+        for (index, waypoint) in self.waypoints.iter().enumerate() {
+            buf.label(|| format!("waypoint {index} altitude and POI"));
             buf.put_i16(waypoint.altitude_mode as i16);
             buf.put_f32(waypoint.altitude);
-            buf.put_i32(waypoint.poi_index.map_or(-1, |index| {
-                index.try_into().expect("POI index must fit into i32")
-            }));
+            buf.put_i32(match waypoint.poi_index {
+                Some(poi_index) => {
+                    poi_index
+                        .try_into()
+                        .map_err(|_| LitchiError::PoiIndexOverflow {
+                            waypoint: index,
+                            poi_index,
+                        })?
+                }
+                None => -1,
+            });
         }
 
         // Set POI altitude info
-        for poi in &self.pois {
+        for (index, poi) in self.pois.iter().enumerate() {
+            buf.label(|| format!("POI {index} altitude"));
             buf.put_i16(poi.altitude_mode as i16);
             buf.put_f32(poi.altitude);
         }
 
         // Magic numbers
 
+        buf.label(|| "magic numbers".to_string());
         buf.put_i32(8);
         buf.put_i32(8);
         buf.put_i32(0);
 
         // Set global photo intervals
 
-        let mut set_interval = |interval: Option<&PhotoInterval>| {
+        let set_interval = |buf: &mut BinaryWriter, interval: Option<&PhotoInterval>| {
             if let Some(interval) = interval {
                 match interval {
                     PhotoInterval::Time(time) => {
@@ -280,69 +1208,436 @@ impl LitchiMission {
             }
         };
 
-        set_interval(self.config.photo_interval.as_ref());
+        buf.label(|| "mission photo interval".to_string());
+        set_interval(buf, self.config.photo_interval.as_ref());
 
-        for waypoint in &self.waypoints {
-            set_interval(waypoint.photo_interval.as_ref());
+        for (index, waypoint) in self.waypoints.iter().enumerate() {
+            buf.label(|| format!("waypoint {index} photo interval"));
+            set_interval(buf, waypoint.photo_interval.as_ref());
         }
 
-        buf.freeze()
+        Ok(())
     }
 }
 
-impl Default for MissionConfig {
-    fn default() -> Self {
+impl ActionSequenceBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a sequence for a waypoint reached while a recording started at an earlier waypoint
+    /// is still running
+    pub fn recording() -> Self {
         Self {
-            heading_mode: HeadingMode::Manual,
-            finish_action: FinishAction::Rth,
-            path_mode: PathMode::StraightLines,
-            cruising_speed: 8.,
-            rc_speed: 14.,
-            n_repeat: 1,
-            version: 11,
-            photo_interval: None,
+            actions: vec![],
+            recording: true,
         }
     }
-}
 
-impl Hash for POI {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.coordinate.hash(state);
-        self.altitude.to_bits().hash(state);
-        self.altitude_mode.hash(state);
+    pub fn push(mut self, action: Action) -> Result<Self, LitchiError> {
+        if self.actions.len() >= MAX_ACTIONS {
+            Err(LitchiError::InvalidActionSequence(format!(
+                "a waypoint can hold at most {MAX_ACTIONS} actions"
+            )))?;
+        }
+
+        match action {
+            Action::StartRecording if self.recording => Err(LitchiError::InvalidActionSequence(
+                "recording was already started".to_string(),
+            ))?,
+            Action::StopRecording if !self.recording => Err(LitchiError::InvalidActionSequence(
+                "recording was stopped before being started".to_string(),
+            ))?,
+            Action::RotateAircraft(heading) if !ROTATION_RANGE.contains(&heading) => {
+                Err(LitchiError::RotationOutOfRange(heading))?
+            }
+            Action::StartRecording => self.recording = true,
+            Action::StopRecording => self.recording = false,
+            _ => (),
+        }
+
+        self.actions.push(action);
+        Ok(self)
     }
-}
 
-impl Hash for Coordinate {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.0.to_bits().hash(state);
-        self.1.to_bits().hash(state);
+    /// Whether a recording is still running after the actions added so far
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    pub fn build(self) -> Vec<Action> {
+        self.actions
     }
 }
 
-impl Action {
-    fn idx_and_param(&self) -> (i32, i32) {
-        match self {
-            Self::StayFor(stay) => (0, (stay * 1000.) as i32),
-            Self::TakePhoto => (1, 0),
-            Self::StartRecording => (2, 0),
-            Self::StopRecording => (3, 0),
-            Self::RotateAircraft(rotation) => (4, *rotation),
-            Self::TiltCamera(tilt) => (5, *tilt),
+impl MissionBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn config(mut self, config: MissionConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Starts a waypoint at the given coordinate and altitude in meters above takeoff
+    pub fn waypoint(self, latitude: f64, longitude: f64, altitude: f32) -> WaypointBuilder {
+        let actions = if self.recording {
+            ActionSequenceBuilder::recording()
+        } else {
+            ActionSequenceBuilder::new()
+        };
+
+        WaypointBuilder {
+            mission: self,
+            waypoint: Waypoint {
+                coordinate: Coordinate(latitude, longitude),
+                altitude,
+                ..Default::default()
+            },
+            actions,
         }
     }
-}
 
-fn degrees_to_radians(degrees: f64) -> f64 {
-    degrees * PI / 180.
-}
+    /// Builds and validates the mission, failing with the first error of a setter or for speeds
+    /// outside of [`MissionConstraints::default`], see [`MissionConfig::validate`]
+    pub fn build(self) -> Result<LitchiMission, LitchiError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.config.validate(&MissionConstraints::default())?;
 
-fn radians_to_degrees(radians: f64) -> f64 {
-    radians * 180. / PI
+        LitchiMission::new(self.waypoints, self.pois, self.config)
+    }
 }
 
-impl Coordinate {
+impl WaypointBuilder {
+    /// Heading in degrees, wrapped into -180..=180 when the mission is built
+    pub fn heading(mut self, heading: f32) -> Self {
+        self.waypoint.heading = heading;
+        self
+    }
+
+    /// Speed in m/s flown to the next waypoint, 0 uses the cruising speed
+    pub fn speed(mut self, speed: f32) -> Self {
+        self.waypoint.speed = speed;
+        self
+    }
+
+    pub fn altitude_mode(mut self, altitude_mode: AltitudeMode) -> Self {
+        self.waypoint.altitude_mode = altitude_mode;
+        self
+    }
+
+    /// Radius in meters of the curve flown through the waypoint with [`PathMode::CurvedTurns`]
+    pub fn curve_size(mut self, curve_size: f32) -> Self {
+        self.waypoint.curve_size = curve_size;
+        self
+    }
+
+    /// Gimbal pitch in degrees, interpolated between waypoints
+    pub fn gimbal_pitch(mut self, angle: i32) -> Self {
+        self.waypoint.gimbal_mode = GimbalPitchMode::Interpolate;
+        self.waypoint.gimbal_pitch_angle = angle;
+        self
+    }
+
+    /// Time to hover at the waypoint, see [`Waypoint::set_stay_duration`]
+    pub fn stay(mut self, duration: Duration) -> Self {
+        if let Err(error) = self.waypoint.set_stay_duration(duration) {
+            self.mission.error.get_or_insert(error);
+        }
+        self
+    }
+
+    pub fn photo_interval(mut self, photo_interval: PhotoInterval) -> Self {
+        self.waypoint.photo_interval = Some(photo_interval);
+        self
+    }
+
+    /// Appends an action, checked like [`ActionSequenceBuilder::push`] with recordings carrying
+    /// over from earlier waypoints
+    pub fn action(mut self, action: Action) -> Self {
+        let actions = std::mem::take(&mut self.actions);
+        match actions.push(action) {
+            Ok(actions) => self.actions = actions,
+            Err(error) => {
+                self.mission.error.get_or_insert(error);
+            }
+        }
+        self
+    }
+
+    /// Focuses the POI with the gimbal and turns the aircraft towards it. The POI is added to
+    /// the mission unless an equal one already is.
+    pub fn poi(mut self, poi: POI) -> Self {
+        let pois = &mut self.mission.pois;
+        let index = pois
+            .iter()
+            .position(|known| known == &poi)
+            .unwrap_or_else(|| {
+                pois.push(poi);
+                pois.len() - 1
+            });
+
+        let coordinate = &pois[index].coordinate;
+        if *coordinate != self.waypoint.coordinate {
+            self.waypoint.heading = self.waypoint.coordinate.heading_towards(coordinate) as f32;
+        }
+        self.waypoint.poi_index = Some(index);
+        self.waypoint.gimbal_mode = GimbalPitchMode::FocusPOI;
+        self
+    }
+
+    /// Adds the waypoint and starts the next one
+    pub fn waypoint(self, latitude: f64, longitude: f64, altitude: f32) -> WaypointBuilder {
+        self.done().waypoint(latitude, longitude, altitude)
+    }
+
+    /// Adds the waypoint, returning to the mission
+    pub fn done(mut self) -> MissionBuilder {
+        self.mission.recording = self.actions.is_recording();
+        self.waypoint.actions = self.actions.build();
+        self.mission.waypoints.push(self.waypoint);
+        self.mission
+    }
+
+    /// Adds the waypoint and builds the mission, see [`MissionBuilder::build`]
+    pub fn build(self) -> Result<LitchiMission, LitchiError> {
+        self.done().build()
+    }
+}
+
+/// Defaults used for fields a source format doesn't provide: a waypoint at (0, 0) and 0 m
+/// relative to takeoff, no heading, curve, gimbal pitch or POI, the cruising speed (0), no actions
+/// or photo interval, a 3 s stay time, no maximum reach time and actions executed once.
+impl Default for Waypoint {
+    fn default() -> Self {
+        Self {
+            coordinate: Coordinate(0., 0.),
+            altitude: 0.,
+            heading: 0.,
+            curve_size: 0.,
+            rotation_dir: 0,
+            gimbal_mode: GimbalPitchMode::Disabled,
+            gimbal_pitch_angle: 0,
+            altitude_mode: AltitudeMode::Absolute,
+            speed: 0.,
+            poi_index: None,
+            actions: vec![],
+            photo_interval: None,
+            turn_mode: 0,
+            stay_time: 3,
+            max_reach_time: 0,
+            repeat_actions: 1,
+        }
+    }
+}
+
+impl MissionConstraints {
+    /// Checks that the speed ranges have finite bounds and don't end before they start, failing
+    /// with [`LitchiError::InvalidMission`] otherwise
+    pub fn validate(&self) -> Result<(), LitchiError> {
+        let ranges = [
+            ("cruising speed", &self.cruising_speed),
+            ("rc speed", &self.rc_speed),
+        ];
+        for (field, range) in ranges {
+            let (start, end) = (*range.start(), *range.end());
+            if !start.is_finite() || !end.is_finite() || start > end {
+                Err(LitchiError::InvalidMission(format!(
+                    "{field} constraint {start}..={end} is not a valid range"
+                )))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for MissionConstraints {
+    fn default() -> Self {
+        Self {
+            cruising_speed: CRUISING_SPEED_RANGE,
+            rc_speed: RC_SPEED_RANGE,
+            max_curve_size: MAX_CURVE_SIZE,
+        }
+    }
+}
+
+impl Default for MissionConfig {
+    fn default() -> Self {
+        Self {
+            heading_mode: HeadingMode::Manual,
+            finish_action: FinishAction::Rth,
+            path_mode: PathMode::StraightLines,
+            cruising_speed: 8.,
+            rc_speed: 14.,
+            n_repeat: 1,
+            version: 11,
+            photo_interval: None,
+            initial_heading: None,
+        }
+    }
+}
+
+impl MissionConfig {
+    /// Checks that the cruising and rc speeds lie within the `constraints`, failing with
+    /// [`LitchiError::InvalidMission`] otherwise. [`MissionBuilder::build`] checks this against
+    /// [`MissionConstraints::default`], while [`LitchiMission::new`] accepts such speeds and
+    /// only clamps them when encoding, see [`LitchiMission::validation_notes`].
+    pub fn validate(&self, constraints: &MissionConstraints) -> Result<(), LitchiError> {
+        let speeds = [
+            (
+                "cruising speed",
+                self.cruising_speed,
+                &constraints.cruising_speed,
+            ),
+            ("rc speed", self.rc_speed, &constraints.rc_speed),
+        ];
+        for (field, speed, range) in speeds {
+            if !range.contains(&speed) {
+                Err(LitchiError::InvalidMission(format!(
+                    "{field} of {speed} m/s is outside of {}..={}",
+                    range.start(),
+                    range.end()
+                )))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Clamps a speed of the config into `range` for encoding, warning when it changes
+fn clamp_speed(field: &str, speed: f32, range: &RangeInclusive<f32>) -> f32 {
+    let clamped = speed.clamp(*range.start(), *range.end());
+    if clamped != speed {
+        warn!(
+            field,
+            speed, clamped, "Clamping speed to the range the app accepts"
+        );
+    }
+    clamped
+}
+
+impl Hash for POI {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.coordinate.hash(state);
+        self.altitude.to_bits().hash(state);
+        self.altitude_mode.hash(state);
+    }
+}
+
+impl Hash for Coordinate {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+        self.1.to_bits().hash(state);
+    }
+}
+
+impl Waypoint {
+    /// Time to hover at the waypoint, negative stay times count as none
+    pub fn stay_duration(&self) -> Duration {
+        Duration::from_secs(self.stay_time.max(0) as u64)
+    }
+
+    /// Sets the time to hover at the waypoint, rounded to whole seconds
+    pub fn set_stay_duration(&mut self, duration: Duration) -> Result<(), LitchiError> {
+        let seconds = duration.as_secs_f64().round();
+        if seconds > i16::MAX as f64 {
+            Err(LitchiError::StayTimeOutOfRange(duration))?;
+        }
+
+        self.stay_time = seconds as i16;
+        Ok(())
+    }
+}
+
+impl Action {
+    /// A [`Action::StayFor`] hovering for the given duration, which must fit in the i32
+    /// milliseconds Litchi stores. The seconds are kept as f32, which holds every millisecond
+    /// only up to about 16 777 s, longer stays are rounded to a multiple of a few milliseconds.
+    pub fn stay_for(duration: Duration) -> Result<Self, LitchiError> {
+        let out_of_range = || LitchiError::StayTimeOutOfRange(duration);
+        if duration.as_millis() > i32::MAX as u128 {
+            Err(out_of_range())?;
+        }
+
+        // The rounded seconds may not fit anymore, so check the action as it will be encoded
+        let action = Self::StayFor(duration.as_millis() as f32 / 1000.);
+        action.idx_and_param().map_err(|_| out_of_range())?;
+        Ok(action)
+    }
+
+    /// Hover time of a [`Action::StayFor`], `None` for every other action
+    pub fn stay_duration(&self) -> Option<Duration> {
+        match self {
+            Self::StayFor(stay) => Some(Duration::from_millis((stay * 1000.).max(0.) as u64)),
+            _ => None,
+        }
+    }
+
+    /// Decodes an action from its Litchi action type and parameter
+    pub(crate) fn from_idx_and_param(action_n: i32, param: i32) -> Result<Self, LitchiError> {
+        Ok(match action_n {
+            0 => Self::StayFor(param as f32 / 1000.),
+            1 => Self::TakePhoto,
+            2 => Self::StartRecording,
+            3 => Self::StopRecording,
+            4 if !ROTATION_RANGE.contains(&param) => Err(LitchiError::RotationOutOfRange(param))?,
+            4 => Self::RotateAircraft(param),
+            5 => Self::TiltCamera(param),
+            action_type => Err(LitchiError::InvalidActionType { action_type, param })?,
+        })
+    }
+
+    /// Encodes the action as its Litchi action type and parameter, failing for stays whose
+    /// milliseconds don't fit the i32 parameter
+    pub(crate) fn idx_and_param(&self) -> Result<(i32, i32), LitchiError> {
+        Ok(match self {
+            Self::StayFor(stay) => {
+                let millis = (*stay as f64 * 1000.).round();
+                if !(i32::MIN as f64..=i32::MAX as f64).contains(&millis) {
+                    Err(LitchiError::StayTimeOutOfRange(
+                        Duration::try_from_secs_f32(stay.abs()).unwrap_or(Duration::MAX),
+                    ))?;
+                }
+                (0, millis as i32)
+            }
+            Self::TakePhoto => (1, 0),
+            Self::StartRecording => (2, 0),
+            Self::StopRecording => (3, 0),
+            Self::RotateAircraft(rotation) => (4, *rotation),
+            Self::TiltCamera(tilt) => (5, *tilt),
+        })
+    }
+}
+
+/// Count of a binary format section, failing with `error` if it doesn't fit into an i32
+fn encode_count(
+    count: usize,
+    error: impl FnOnce(usize) -> LitchiError,
+) -> Result<i32, LitchiError> {
+    count.try_into().map_err(|_| error(count))
+}
+
+fn degrees_to_radians(degrees: f64) -> f64 {
+    degrees * PI / 180.
+}
+
+fn radians_to_degrees(radians: f64) -> f64 {
+    radians * 180. / PI
+}
+
+impl Coordinate {
+    /// Initial bearing towards `other` in degrees within -180..=180, 0 (north) if both are the
+    /// same coordinate
     pub fn heading_towards(&self, other: &Coordinate) -> f64 {
+        if self == other {
+            return 0.;
+        }
+
         let lat1 = degrees_to_radians(self.0);
         let lat2 = degrees_to_radians(other.0);
         let delta_lon = degrees_to_radians(other.1 - self.1);
@@ -353,8 +1648,1348 @@ impl Coordinate {
         radians_to_degrees(y.atan2(x))
     }
 
+    /// Great-circle distance in meters, using the haversine formula
+    pub fn distance_to(&self, other: &Coordinate) -> f64 {
+        let lat1 = degrees_to_radians(self.0);
+        let lat2 = degrees_to_radians(other.0);
+        let delta_lat = lat2 - lat1;
+        let delta_lon = degrees_to_radians(other.1 - self.1);
+
+        let a = (delta_lat / 2.).sin().powi(2)
+            + lat1.cos() * lat2.cos() * (delta_lon / 2.).sin().powi(2);
+
+        // Rounding can push nearly antipodal points slightly above 1, where asin is NaN
+        2. * EARTH_RADIUS * a.clamp(0., 1.).sqrt().asin()
+    }
+
+    /// Distance in meters to the great-circle leg from `start` to `end`: to the closest point in
+    /// between, or to the nearer end if the coordinate lies before or beyond the leg
+    fn distance_to_leg(&self, start: &Coordinate, end: &Coordinate) -> f64 {
+        let from_start = start.distance_to(self);
+        let leg = start.distance_to(end);
+        if leg == 0. || from_start == 0. {
+            return from_start;
+        }
+
+        let bearing_difference =
+            degrees_to_radians(start.heading_towards(self) - start.heading_towards(end));
+        if bearing_difference.cos() < 0. {
+            return from_start;
+        }
+
+        let angular_distance = from_start / EARTH_RADIUS;
+        let cross_track = (angular_distance.sin() * bearing_difference.sin()).asin();
+        let along_track = (angular_distance.cos() / cross_track.cos())
+            .clamp(-1., 1.)
+            .acos()
+            * EARTH_RADIUS;
+
+        if along_track > leg {
+            self.distance_to(end)
+        } else {
+            cross_track.abs() * EARTH_RADIUS
+        }
+    }
+
+    /// The coordinate moved by the given distances in meters, treating the earth as flat around
+    /// it. Accurate for the few kilometers a mission spans.
+    pub fn offset(&self, north: f64, east: f64) -> Coordinate {
+        let latitude = self.0 + radians_to_degrees(north / EARTH_RADIUS);
+        let longitude =
+            self.1 + radians_to_degrees(east / (EARTH_RADIUS * degrees_to_radians(latitude).cos()));
+
+        Coordinate(latitude, longitude)
+    }
+
+    /// Inverse of [`Coordinate::offset`]: how far north and east of `origin` the coordinate is
+    pub fn offset_from(&self, origin: &Coordinate) -> (f64, f64) {
+        let north = degrees_to_radians(self.0 - origin.0) * EARTH_RADIUS;
+        let east =
+            degrees_to_radians(self.1 - origin.1) * EARTH_RADIUS * degrees_to_radians(self.0).cos();
+
+        (north, east)
+    }
+
+    /// Formats the coordinate in degrees, minutes and seconds, like `48°08'13.8" N, 11°34'34.0" E`
+    pub fn to_dms(&self) -> String {
+        let dms = |degrees: f64, positive: char, negative: char| {
+            let hemisphere = if degrees < 0. { negative } else { positive };
+            // Round once in tenths of a second, so 59.95" carries over into the minutes
+            let tenths = (degrees.abs() * 36_000.).round() as u64;
+            format!(
+                "{}°{:02}'{:02}.{}\" {hemisphere}",
+                tenths / 36_000,
+                tenths % 36_000 / 600,
+                tenths % 600 / 10,
+                tenths % 10
+            )
+        };
+
+        format!("{}, {}", dms(self.0, 'N', 'S'), dms(self.1, 'E', 'W'))
+    }
+
+    /// Whether the latitude is within -90..=90 and the longitude within -180..=180
     pub fn valid(&self) -> bool {
-        const VALID_RANGE: std::ops::Range<f64> = (-180.)..180.;
-        VALID_RANGE.contains(&self.0) && VALID_RANGE.contains(&self.1)
+        (-90. ..=90.).contains(&self.0) && (-180. ..=180.).contains(&self.1)
+    }
+}
+
+/// Formats the coordinate in decimal degrees, like `48.137154° N, 11.576124° E`
+impl fmt::Display for Coordinate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let latitude = if self.0 < 0. { 'S' } else { 'N' };
+        let longitude = if self.1 < 0. { 'W' } else { 'E' };
+
+        write!(
+            f,
+            "{:.6}° {latitude}, {:.6}° {longitude}",
+            self.0.abs(),
+            self.1.abs()
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for LitchiMission {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        struct Fields {
+            waypoints: Vec<Waypoint>,
+            pois: Vec<POI>,
+            config: MissionConfig,
+            #[serde(default)]
+            constraints: MissionConstraints,
+        }
+
+        let fields = Fields::deserialize(deserializer)?;
+        let mission = Self {
+            waypoints: fields.waypoints,
+            pois: fields.pois,
+            config: fields.config,
+            constraints: fields.constraints,
+        };
+        mission.validate().map_err(serde::de::Error::custom)?;
+        Ok(mission)
+    }
+}
+
+/// One-line summary like `3 waypoints, 1 POIs, 1234 m, heading mode Auto, finish action Rth,
+/// cruising at 10 m/s`, see [`LitchiMission::total_distance`]
+impl fmt::Display for LitchiMission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} waypoints, {} POIs, {:.0} m, heading mode {:?}, finish action {:?}, cruising at {} m/s",
+            self.waypoints.len(),
+            self.pois.len(),
+            self.total_distance(),
+            self.config.heading_mode,
+            self.config.finish_action,
+            self.config.cruising_speed
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::ValidationIssue;
+
+    fn waypoint(latitude: f64, longitude: f64) -> Waypoint {
+        Waypoint {
+            coordinate: Coordinate(latitude, longitude),
+            altitude: 30.,
+            altitude_mode: AltitudeMode::AboveGround,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_append_return_path() {
+        let poi = POI {
+            coordinate: Coordinate(48.0, 11.0),
+            altitude: 10.,
+            altitude_mode: AltitudeMode::AboveGround,
+        };
+        let mut poi_waypoint = waypoint(48.001, 11.0);
+        poi_waypoint.poi_index = Some(0);
+
+        let mut mission = LitchiMission::new(
+            vec![waypoint(48.0, 11.0), poi_waypoint, waypoint(48.002, 11.0)],
+            vec![poi],
+            MissionConfig::default(),
+        )
+        .unwrap();
+
+        mission.append_return_path();
+
+        let waypoints = mission.waypoints();
+        assert_eq!(waypoints.len(), 5);
+        assert_eq!(waypoints[4].coordinate, waypoints[0].coordinate);
+        assert_eq!(waypoints[3].poi_index, Some(0));
+        // Flying back south
+        assert!((waypoints[4].heading.abs() - 180.).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_strip_pois() {
+        let mut poi_waypoint = waypoint(48.001, 11.0);
+        poi_waypoint.poi_index = Some(0);
+        poi_waypoint.gimbal_mode = GimbalPitchMode::FocusPOI;
+        poi_waypoint.heading = 42.;
+
+        let mut mission = LitchiMission::new(
+            vec![waypoint(48.0, 11.0), poi_waypoint],
+            vec![POI {
+                coordinate: Coordinate(48.0, 11.0),
+                altitude: 10.,
+                altitude_mode: AltitudeMode::AboveGround,
+            }],
+            MissionConfig::default(),
+        )
+        .unwrap();
+
+        mission.strip_pois();
+
+        assert!(mission.pois().is_empty());
+        assert!(mission.waypoints().iter().all(|waypoint| {
+            waypoint.poi_index.is_none()
+                && !matches!(waypoint.gimbal_mode, GimbalPitchMode::FocusPOI)
+        }));
+        assert_eq!(mission.waypoints()[1].heading, 42.);
+    }
+
+    #[test]
+    fn test_coordinate_formatting() {
+        let munich = Coordinate(48.137154, 11.576124);
+        assert_eq!(munich.to_dms(), "48°08'13.8\" N, 11°34'34.0\" E");
+        assert_eq!(munich.to_string(), "48.137154° N, 11.576124° E");
+
+        let sydney = Coordinate(-33.8688, 151.2093);
+        assert_eq!(sydney.to_dms(), "33°52'07.7\" S, 151°12'33.5\" E");
+        assert_eq!(
+            Coordinate(40.7128, -74.006).to_string(),
+            "40.712800° N, 74.006000° W"
+        );
+    }
+
+    #[test]
+    fn test_heading_towards() {
+        let origin = Coordinate(48.0, 11.0);
+        let close = |heading: f64, expected: f64| (heading - expected).abs() < 1e-6;
+
+        assert!(close(origin.heading_towards(&Coordinate(49.0, 11.0)), 0.));
+        assert!(close(
+            origin.heading_towards(&Coordinate(47.0, 11.0)).abs(),
+            180.
+        ));
+        // Due east along the equator, away from it the great circle starts slightly north
+        assert!(close(
+            Coordinate(0., 0.).heading_towards(&Coordinate(0., 1.)),
+            90.
+        ));
+        assert!(close(
+            Coordinate(0., 0.).heading_towards(&Coordinate(0., -1.)),
+            -90.
+        ));
+        assert!((origin.heading_towards(&Coordinate(48.0, 12.0)) - 89.628).abs() < 1e-3);
+        assert!((Coordinate(0., 0.).heading_towards(&Coordinate(1., 1.)) - 44.9956).abs() < 1e-4);
+
+        // Crossing the antimeridian takes the short way around
+        let west = Coordinate(0., 179.5);
+        let east = Coordinate(0., -179.5);
+        assert!(close(west.heading_towards(&east), 90.));
+        assert!(close(east.heading_towards(&west), -90.));
+    }
+
+    #[test]
+    fn test_identical_coordinates() {
+        let coordinate = Coordinate(48.0, 11.0);
+
+        assert_eq!(coordinate.heading_towards(&coordinate.clone()), 0.);
+        assert_eq!(coordinate.distance_to(&coordinate.clone()), 0.);
+
+        let antipode = Coordinate(-48.0, -169.0);
+        assert!(coordinate.heading_towards(&antipode).is_finite());
+        assert!(coordinate.distance_to(&antipode).is_finite());
+    }
+
+    #[test]
+    fn test_distance_to() {
+        let within = |distance: f64, expected: f64| (distance - expected).abs() / expected < 0.005;
+
+        let paris = Coordinate(48.8566, 2.3522);
+        let london = Coordinate(51.5074, -0.1278);
+        assert!(within(paris.distance_to(&london), 343_500.));
+        assert!(within(london.distance_to(&paris), 343_500.));
+
+        let new_york = Coordinate(40.7128, -74.006);
+        let los_angeles = Coordinate(34.0522, -118.2437);
+        assert!(within(new_york.distance_to(&los_angeles), 3_936_000.));
+
+        // One degree of longitude on the equator, across the antimeridian
+        let west = Coordinate(0., 179.5);
+        let east = Coordinate(0., -179.5);
+        assert!(within(west.distance_to(&east), EARTH_RADIUS * PI / 180.));
+    }
+
+    #[test]
+    fn test_center_on() {
+        let mut mission = LitchiMission::new(
+            vec![
+                waypoint(48.0, 11.0),
+                waypoint(48.001, 11.0),
+                waypoint(48.001, 11.002),
+            ],
+            vec![],
+            MissionConfig::default(),
+        )
+        .unwrap();
+        let leg = mission.waypoints()[1]
+            .coordinate
+            .distance_to(&mission.waypoints()[2].coordinate);
+
+        let new_center = Coordinate(33.6, 5.9);
+        mission.center_on(new_center.clone());
+
+        let centroid = mission.centroid().expect("Mission has waypoints");
+        assert!(centroid.distance_to(&new_center) < 0.5);
+        let moved_leg = mission.waypoints()[1]
+            .coordinate
+            .distance_to(&mission.waypoints()[2].coordinate);
+        assert!((moved_leg - leg).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_waypoints_with_pois() {
+        let poi = |latitude: f64| POI {
+            coordinate: Coordinate(latitude, 11.0),
+            altitude: 10.,
+            altitude_mode: AltitudeMode::AboveGround,
+        };
+        let mut second = waypoint(48.001, 11.0);
+        second.poi_index = Some(1);
+        let mut third = waypoint(48.002, 11.0);
+        third.poi_index = Some(0);
+
+        let mission = LitchiMission::new(
+            vec![waypoint(48.0, 11.0), second, third],
+            vec![poi(48.1), poi(48.2)],
+            MissionConfig::default(),
+        )
+        .unwrap();
+
+        let pois: Vec<_> = mission
+            .waypoints_with_pois()
+            .map(|(_, poi)| poi.map(|poi| poi.coordinate.0))
+            .collect();
+        assert_eq!(pois, [None, Some(48.2), Some(48.1)]);
+
+        let mut dangling = waypoint(48.003, 11.0);
+        dangling.poi_index = Some(5);
+        let unchecked = LitchiMission {
+            waypoints: vec![dangling],
+            ..mission
+        };
+        assert!(unchecked
+            .waypoints_with_pois()
+            .all(|(_, poi)| poi.is_none()));
+    }
+
+    #[test]
+    fn test_speed_constraints() {
+        let mission = LitchiMission::new(
+            vec![waypoint(48.0, 11.0), waypoint(48.001, 11.0)],
+            vec![],
+            MissionConfig {
+                cruising_speed: 18.,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let clamped = LitchiMission::from_binary(&mission.to_binary()).unwrap();
+        assert_eq!(clamped.config().cruising_speed, 15.);
+        assert_eq!(
+            mission.validation_notes(),
+            [ValidationIssue::SpeedClamped {
+                field: "cruising speed",
+                speed: 18.
+            }]
+        );
+
+        let mission = mission
+            .with_constraints(MissionConstraints {
+                cruising_speed: -20.0..=20.,
+                ..Default::default()
+            })
+            .unwrap();
+        let kept = LitchiMission::from_binary(&mission.to_binary()).unwrap();
+        assert_eq!(kept.config().cruising_speed, 18.);
+        assert!(mission.validation_notes().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_constraints() {
+        let mission =
+            LitchiMission::new(vec![waypoint(48.0, 11.0)], vec![], MissionConfig::default())
+                .unwrap();
+
+        for constraints in [
+            MissionConstraints {
+                cruising_speed: 15.0..=-15.,
+                ..Default::default()
+            },
+            MissionConstraints {
+                rc_speed: f32::NAN..=15.,
+                ..Default::default()
+            },
+        ] {
+            assert!(matches!(
+                mission.clone().with_constraints(constraints.clone()),
+                Err(LitchiError::InvalidMission(_))
+            ));
+
+            let unchecked = LitchiMission {
+                constraints,
+                ..mission.clone()
+            };
+            assert!(matches!(
+                unchecked.validate(),
+                Err(LitchiError::InvalidMission(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_config_speed_validation() {
+        let constraints = MissionConstraints::default();
+        assert!(MissionConfig::default().validate(&constraints).is_ok());
+
+        let too_fast = MissionConfig {
+            cruising_speed: 20.,
+            ..Default::default()
+        };
+        assert!(matches!(
+            too_fast.validate(&constraints),
+            Err(LitchiError::InvalidMission(reason)) if reason.contains("cruising speed")
+        ));
+        let result = MissionBuilder::new()
+            .config(too_fast)
+            .waypoint(48.0, 11.0, 30.)
+            .build();
+        assert!(matches!(result, Err(LitchiError::InvalidMission(_))));
+
+        let too_slow = MissionConfig {
+            rc_speed: 1.,
+            ..Default::default()
+        };
+        assert!(matches!(
+            too_slow.validate(&constraints),
+            Err(LitchiError::InvalidMission(reason)) if reason.contains("rc speed")
+        ));
+    }
+
+    #[test]
+    fn test_estimated_duration() {
+        let start = Coordinate(48.0, 11.0);
+        let line = |speed: f32| {
+            [0., 100., 300.]
+                .into_iter()
+                .map(|north| Waypoint {
+                    coordinate: start.offset(north, 0.),
+                    speed,
+                    stay_time: 0,
+                    ..Default::default()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut waypoints = line(10.);
+        waypoints[1].actions.push(Action::StayFor(5.));
+        waypoints[2].stay_time = 2;
+        let mission = LitchiMission::new(waypoints, vec![], MissionConfig::default()).unwrap();
+        assert!((mission.total_distance() - 300.).abs() < 0.1);
+        let duration = mission.estimated_duration().as_secs_f64();
+        assert!((duration - 37.).abs() < 0.1, "{duration}");
+
+        // Waypoints without speed fly at the cruising speed, 8 m/s by default
+        let reverse = LitchiMission::new(
+            line(0.),
+            vec![],
+            MissionConfig {
+                finish_action: FinishAction::Reverse,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!((reverse.total_distance() - 600.).abs() < 0.1);
+        assert!((reverse.estimated_duration().as_secs_f64() - 75.).abs() < 0.1);
+
+        let looped = LitchiMission::new(
+            line(10.),
+            vec![],
+            MissionConfig {
+                finish_action: FinishAction::BackToFirst,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!((looped.total_distance() - 600.).abs() < 0.1);
+        assert!((looped.estimated_duration().as_secs_f64() - 60.).abs() < 0.1);
+
+        let single =
+            LitchiMission::new(line(10.)[..1].to_vec(), vec![], looped.config().clone()).unwrap();
+        assert_eq!(single.total_distance(), 0.);
+        assert_eq!(single.estimated_duration(), Duration::ZERO);
+
+        let crawling = LitchiMission::new(line(1e-30), vec![], MissionConfig::default()).unwrap();
+        assert_eq!(crawling.estimated_duration(), Duration::MAX);
+    }
+
+    #[test]
+    fn test_estimated_battery_percent() {
+        let drone = DroneProfile {
+            cruise_draw_wh_per_km: 3.,
+            hover_draw_w: 120.,
+            battery_wh: 60.,
+        };
+        let start = Coordinate(48.137154, 11.576124);
+        // 2 km leg with a 3 minute stay: 6 Wh cruising and 6 Wh hovering
+        let mut waypoints = vec![
+            Waypoint {
+                coordinate: start.clone(),
+                stay_time: 60,
+                ..Default::default()
+            },
+            Waypoint {
+                coordinate: start.offset(2000., 0.),
+                stay_time: 0,
+                ..Default::default()
+            },
+        ];
+        waypoints[1].actions.push(Action::StayFor(120.));
+        let mission = LitchiMission::new(waypoints, vec![], MissionConfig::default()).unwrap();
+
+        assert!((mission.total_distance() - 2000.).abs() < 1.);
+        let percent = mission.estimated_battery_percent(drone);
+        assert!((percent - 20.).abs() < 0.1, "{percent}");
+        assert!(mission.estimated_battery_percent(DroneProfile::MINI_3_PRO) < 100.);
+    }
+
+    #[test]
+    fn test_insert_stay() {
+        let mut mission = LitchiMission::new(
+            vec![waypoint(48.0, 11.0), waypoint(48.001, 11.0)],
+            vec![],
+            MissionConfig::default(),
+        )
+        .unwrap();
+
+        mission
+            .insert_stay(1, Duration::from_secs(10))
+            .expect("Waypoint has room for the stay");
+
+        let actions = &mission.waypoints()[1].actions;
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].stay_duration(), Some(Duration::from_secs(10)));
+        assert!(matches!(
+            mission.insert_stay(2, Duration::from_secs(10)),
+            Err(LitchiError::InvalidWaypointIndex(2))
+        ));
+    }
+
+    #[test]
+    fn test_bounding_circle() {
+        let mission = LitchiMission::new(
+            vec![
+                waypoint(48.0, 11.0),
+                waypoint(48.001, 11.0),
+                waypoint(48.001, 11.002),
+                waypoint(48.005, 11.01),
+            ],
+            vec![],
+            MissionConfig::default(),
+        )
+        .unwrap();
+
+        let (center, radius) = mission.bounding_circle().expect("Mission has waypoints");
+
+        assert!(radius > 0.);
+        assert!(mission
+            .waypoints()
+            .iter()
+            .all(|waypoint| center.distance_to(&waypoint.coordinate) <= radius));
+        assert!(LitchiMission::default().bounding_circle().is_none());
+    }
+
+    #[test]
+    fn test_photos_per_leg() {
+        // Legs of roughly 111 m each
+        let mut first = waypoint(48.0, 11.0);
+        first.photo_interval = Some(PhotoInterval::Distance(10.));
+        first.actions = vec![Action::TakePhoto];
+        let mut second = waypoint(48.001, 11.0);
+        second.photo_interval = Some(PhotoInterval::Distance(50.));
+        let third = waypoint(48.002, 11.0);
+
+        let mission =
+            LitchiMission::new(vec![first, second, third], vec![], MissionConfig::default())
+                .unwrap();
+
+        assert_eq!(mission.photos_per_leg(), [12, 2, 0]);
+        assert_eq!(mission.photo_count_estimate(), 14);
+
+        let timed = |cruising_speed: f32| {
+            LitchiMission::new(
+                vec![waypoint(48.0, 11.0), waypoint(48.001, 11.0)],
+                vec![],
+                MissionConfig {
+                    cruising_speed,
+                    photo_interval: Some(PhotoInterval::Time(2.)),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+        // 111 m at 10 m/s take 11 s, backwards as well
+        assert_eq!(timed(10.).photos_per_leg(), [5, 0]);
+        assert_eq!(timed(-10.).photos_per_leg(), [5, 0]);
+        assert_eq!(timed(0.).photos_per_leg(), [0, 0]);
+        assert_eq!(timed(0.).photo_count_estimate(), 0);
+    }
+
+    #[test]
+    fn test_clamp_to_geofence() {
+        let pois = vec![
+            POI {
+                coordinate: Coordinate(48.5, 11.0),
+                altitude: 10.,
+                altitude_mode: AltitudeMode::AboveGround,
+            },
+            POI {
+                coordinate: Coordinate(48.0, 11.0),
+                altitude: 10.,
+                altitude_mode: AltitudeMode::AboveGround,
+            },
+        ];
+        let mut outside = waypoint(48.5, 11.0);
+        outside.poi_index = Some(0);
+        let mut inside = waypoint(48.002, 11.0);
+        inside.poi_index = Some(1);
+
+        let mut mission = LitchiMission::new(
+            vec![waypoint(48.0, 11.0), outside, inside],
+            pois,
+            MissionConfig::default(),
+        )
+        .unwrap();
+
+        let removed = mission
+            .clamp_to_geofence(
+                (Coordinate(47.9, 10.9), Coordinate(48.1, 11.1)),
+                GeofenceMode::Remove,
+            )
+            .unwrap();
+
+        assert_eq!(removed, 1);
+        assert_eq!(mission.waypoints().len(), 2);
+        assert_eq!(mission.pois().len(), 1);
+        assert_eq!(mission.waypoints()[1].poi_index, Some(0));
+        assert_eq!(mission.pois()[0].coordinate, Coordinate(48.0, 11.0));
+    }
+
+    #[test]
+    fn test_invalid_geofence() {
+        let mut mission = LitchiMission::new(
+            vec![waypoint(48.0, 11.0), waypoint(48.001, 11.0)],
+            vec![],
+            MissionConfig::default(),
+        )
+        .unwrap();
+
+        for bbox in [
+            (Coordinate(48.1, 11.0), Coordinate(48.0, 11.1)),
+            (Coordinate(48.0, 11.1), Coordinate(48.1, 11.0)),
+            (Coordinate(f64::NAN, 11.0), Coordinate(48.1, 11.1)),
+        ] {
+            for mode in [GeofenceMode::Clamp, GeofenceMode::Remove] {
+                assert!(matches!(
+                    mission.clamp_to_geofence(bbox.clone(), mode),
+                    Err(LitchiError::InvalidGeofence(_))
+                ));
+            }
+        }
+        assert_eq!(mission.waypoints().len(), 2);
+    }
+
+    #[test]
+    fn test_custom_heading_round_trip() {
+        let mut poi_waypoint = waypoint(48.001, 11.0);
+        poi_waypoint.poi_index = Some(0);
+        poi_waypoint.heading = 45.;
+        let mut second = waypoint(48.002, 11.0);
+        second.heading = -135.5;
+
+        let mission = LitchiMission::new(
+            vec![poi_waypoint, second],
+            vec![POI {
+                coordinate: Coordinate(48.0, 11.0),
+                altitude: 10.,
+                altitude_mode: AltitudeMode::AboveGround,
+            }],
+            MissionConfig {
+                heading_mode: HeadingMode::Custom,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let parsed = LitchiMission::from_binary(&mission.to_binary()).unwrap();
+
+        assert_eq!(parsed.config().heading_mode, HeadingMode::Custom);
+        let headings: Vec<_> = parsed.waypoints().iter().map(|w| w.heading).collect();
+        assert_eq!(headings, [45., -135.5]);
+    }
+
+    #[test]
+    fn test_initial_heading_round_trip() {
+        let config = MissionConfig {
+            heading_mode: HeadingMode::Initial,
+            initial_heading: Some(-45.),
+            ..Default::default()
+        };
+        let mission = LitchiMission::new(
+            vec![waypoint(48.0, 11.0), waypoint(48.001, 11.0)],
+            vec![],
+            config.clone(),
+        )
+        .unwrap();
+
+        let binary = mission.to_binary();
+        let parsed = LitchiMission::from_binary(&binary).unwrap();
+
+        assert_eq!(parsed.config().initial_heading, None);
+        assert_eq!(parsed.waypoints()[0].heading, -45.);
+        assert_eq!(parsed.waypoints()[1].heading, 0.);
+        assert_eq!(parsed.to_binary(), binary);
+
+        // Without an initial heading the first waypoint's own heading is kept
+        let mut waypoints = vec![waypoint(48.0, 11.0), waypoint(48.001, 11.0)];
+        waypoints[0].heading = 30.;
+        let mission = LitchiMission::new(
+            waypoints,
+            vec![],
+            MissionConfig {
+                initial_heading: None,
+                ..config.clone()
+            },
+        )
+        .unwrap();
+        let parsed = LitchiMission::from_binary(&mission.to_binary()).unwrap();
+        assert_eq!(parsed.config().initial_heading, None);
+        assert_eq!(parsed.waypoints()[0].heading, 30.);
+        assert_eq!(parsed.to_binary(), mission.to_binary());
+
+        let out_of_range = LitchiMission::new(
+            vec![waypoint(48.0, 11.0)],
+            vec![],
+            MissionConfig {
+                initial_heading: Some(200.),
+                ..config
+            },
+        );
+        assert!(matches!(out_of_range, Err(LitchiError::InvalidMission(_))));
+    }
+
+    #[test]
+    fn test_normalize_heading() {
+        for (heading, expected) in [
+            (181., -179.),
+            (-181., 179.),
+            (360., 0.),
+            (540., 180.),
+            (270., -90.),
+            (-180., -180.),
+            (42.5, 42.5),
+        ] {
+            assert_eq!(normalize_heading(heading), expected, "heading {heading}");
+        }
+
+        let mission = MissionBuilder::new()
+            .config(MissionConfig {
+                heading_mode: HeadingMode::Custom,
+                ..Default::default()
+            })
+            .waypoint(48.0, 11.0, 30.)
+            .heading(181.)
+            .waypoint(48.001, 11.0, 30.)
+            .heading(-181.)
+            .waypoint(48.002, 11.0, 30.)
+            .heading(360.)
+            .waypoint(48.003, 11.0, 30.)
+            .heading(540.)
+            .build()
+            .expect("Headings out of range should be wrapped, not rejected");
+
+        let headings: Vec<_> = mission.waypoints().iter().map(|w| w.heading).collect();
+        assert_eq!(headings, [-179., 179., 0., 180.]);
+    }
+
+    #[test]
+    fn test_mission_builder() {
+        let poi = POI {
+            coordinate: Coordinate(48.001, 11.001),
+            altitude: 10.,
+            altitude_mode: AltitudeMode::AboveGround,
+        };
+        let mission = MissionBuilder::new()
+            .config(MissionConfig {
+                cruising_speed: 5.,
+                ..Default::default()
+            })
+            .waypoint(48.0, 11.0, 30.)
+            .action(Action::StartRecording)
+            .waypoint(48.001, 11.0, 40.)
+            .poi(poi.clone())
+            .stay(Duration::from_secs(10))
+            .waypoint(48.002, 11.0, 40.)
+            .poi(poi)
+            .action(Action::StopRecording)
+            .build()
+            .expect("Builder should produce a valid mission");
+
+        assert_eq!(mission.config().cruising_speed, 5.);
+        assert_eq!(mission.waypoints().len(), 3);
+        assert_eq!(mission.pois().len(), 1);
+        let second = &mission.waypoints()[1];
+        assert_eq!(second.poi_index, Some(0));
+        assert_eq!(second.gimbal_mode, GimbalPitchMode::FocusPOI);
+        assert!((second.heading - 90.).abs() < 0.1);
+        assert_eq!(second.stay_duration(), Duration::from_secs(10));
+        assert_eq!(mission.waypoints()[2].actions, [Action::StopRecording]);
+
+        let result = MissionBuilder::new()
+            .waypoint(48.0, 11.0, 30.)
+            .action(Action::StopRecording)
+            .build();
+        assert!(matches!(result, Err(LitchiError::InvalidActionSequence(_))));
+    }
+
+    #[test]
+    fn test_restore_headings() {
+        let mut first = waypoint(48.0, 11.0);
+        first.heading = 10.;
+        let mut second = waypoint(48.001, 11.0);
+        second.heading = -20.;
+        let mut mission = LitchiMission::new(
+            vec![first, second],
+            vec![POI {
+                coordinate: Coordinate(48.001, 11.001),
+                altitude: 10.,
+                altitude_mode: AltitudeMode::AboveGround,
+            }],
+            MissionConfig::default(),
+        )
+        .unwrap();
+        let saved: Vec<_> = mission.waypoints().iter().map(|w| w.heading).collect();
+
+        // Face the POI, like importing does
+        for waypoint in &mut mission.waypoints {
+            waypoint.poi_index = Some(0);
+            waypoint.heading = waypoint
+                .coordinate
+                .heading_towards(&mission.pois[0].coordinate) as f32;
+        }
+        assert!((mission.waypoints()[1].heading - 90.).abs() < 0.1);
+
+        mission.restore_headings(&saved).unwrap();
+        let headings: Vec<_> = mission.waypoints().iter().map(|w| w.heading).collect();
+        assert_eq!(headings, [10., -20.]);
+        assert_eq!(mission.waypoints()[1].poi_index, Some(0));
+
+        assert!(matches!(
+            mission.restore_headings(&[0.]),
+            Err(LitchiError::HeadingCountMismatch(1, 2))
+        ));
+    }
+
+    #[test]
+    fn test_custom_heading_out_of_range() {
+        let mut out_of_range = waypoint(48.0, 11.0);
+        out_of_range.heading = 270.;
+
+        let config = MissionConfig {
+            heading_mode: HeadingMode::Custom,
+            ..Default::default()
+        };
+
+        let wrapped = LitchiMission::new(vec![out_of_range.clone()], vec![], config.clone())
+            .expect("Heading should be wrapped into range");
+        assert_eq!(wrapped.waypoints()[0].heading, -90.);
+
+        // Missions not created through `new` are still checked
+        let unchecked = LitchiMission {
+            waypoints: vec![out_of_range],
+            config,
+            ..Default::default()
+        };
+        assert!(matches!(
+            unchecked.validate(),
+            Err(LitchiError::InvalidWaypoint { waypoint: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_waypoints() {
+        let invalid_index = |edit: fn(&mut Waypoint)| {
+            let mut invalid = waypoint(48.001, 11.0);
+            edit(&mut invalid);
+            match LitchiMission::new(
+                vec![waypoint(48.0, 11.0), invalid],
+                vec![],
+                MissionConfig::default(),
+            ) {
+                Err(LitchiError::InvalidWaypoint { waypoint, reason }) => {
+                    assert!(!reason.is_empty());
+                    Some(waypoint)
+                }
+                _ => None,
+            }
+        };
+
+        assert_eq!(invalid_index(|w| w.coordinate.0 = 91.), Some(1));
+        assert_eq!(invalid_index(|w| w.coordinate.1 = -180.5), Some(1));
+        assert_eq!(invalid_index(|w| w.altitude = -5.), Some(1));
+        assert_eq!(invalid_index(|w| w.speed = 20.), Some(1));
+        assert_eq!(invalid_index(|w| w.speed = -20.), Some(1));
+        assert_eq!(invalid_index(|w| w.speed = -10.), None);
+        assert_eq!(invalid_index(|w| w.poi_index = Some(0)), Some(1));
+        // Below the takeoff point is fine, headings only matter with HeadingMode::Custom
+        assert_eq!(
+            invalid_index(|w| {
+                w.altitude_mode = AltitudeMode::Absolute;
+                w.altitude = -5.;
+                w.heading = 270.;
+            }),
+            None
+        );
+    }
+
+    #[test]
+    fn test_stay_duration() {
+        let mut waypoint = waypoint(0., 0.);
+        waypoint
+            .set_stay_duration(Duration::from_millis(2600))
+            .expect("Stay time should fit");
+        assert_eq!(waypoint.stay_time, 3);
+        assert_eq!(waypoint.stay_duration(), Duration::from_secs(3));
+
+        let action = Action::stay_for(Duration::from_millis(2600)).expect("Stay time should fit");
+        assert_eq!(action.idx_and_param().unwrap(), (0, 2600));
+        assert_eq!(action.stay_duration(), Some(Duration::from_millis(2600)));
+        assert_eq!(Action::TakePhoto.stay_duration(), None);
+    }
+
+    #[test]
+    fn test_stay_duration_out_of_range() {
+        let mut waypoint = waypoint(0., 0.);
+        assert!(matches!(
+            waypoint.set_stay_duration(Duration::from_secs(i16::MAX as u64 + 1)),
+            Err(LitchiError::StayTimeOutOfRange(_))
+        ));
+        assert_eq!(waypoint.stay_time, 3);
+
+        assert!(matches!(
+            Action::stay_for(Duration::from_secs(3_000_000)),
+            Err(LitchiError::StayTimeOutOfRange(_))
+        ));
+
+        // i32::MAX milliseconds round up to 2147483.75 s as f32
+        let longest = Duration::from_millis(i32::MAX as u64);
+        assert!(matches!(
+            Action::stay_for(longest),
+            Err(LitchiError::StayTimeOutOfRange(duration)) if duration == longest
+        ));
+        let mut mission =
+            LitchiMission::new(vec![waypoint], vec![], MissionConfig::default()).unwrap();
+        assert!(mission.insert_stay(0, longest).is_err());
+        assert!(mission.waypoints()[0].actions.is_empty());
+
+        let long = Action::stay_for(Duration::from_secs(2_147_483)).expect("Stay should fit");
+        assert_eq!(long.idx_and_param().unwrap(), (0, 2_147_483_000));
+    }
+
+    #[test]
+    fn test_stay_duration_too_long() {
+        let stay = Action::StayFor(3_000_000.);
+        assert!(matches!(
+            stay.idx_and_param(),
+            Err(LitchiError::StayTimeOutOfRange(duration)) if duration.as_secs() == 3_000_000
+        ));
+
+        let mut hovering = waypoint(48.0, 11.0);
+        hovering.actions = vec![stay];
+        assert!(matches!(
+            LitchiMission::new(vec![hovering], vec![], MissionConfig::default()),
+            Err(LitchiError::StayTimeOutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn test_rotate_aircraft() {
+        for heading in [-90, 270] {
+            let action = Action::RotateAircraft(heading);
+            let (action_n, param) = action.idx_and_param().unwrap();
+            assert!(matches!(
+                Action::from_idx_and_param(action_n, param),
+                Ok(Action::RotateAircraft(decoded)) if decoded == heading
+            ));
+        }
+
+        assert!(matches!(
+            Action::from_idx_and_param(4, 400),
+            Err(LitchiError::RotationOutOfRange(400))
+        ));
+        assert!(matches!(
+            ActionSequenceBuilder::new().push(Action::RotateAircraft(-200)),
+            Err(LitchiError::RotationOutOfRange(-200))
+        ));
+    }
+
+    #[test]
+    fn test_valid_action_sequence() {
+        let actions = ActionSequenceBuilder::new()
+            .push(Action::StartRecording)
+            .and_then(|builder| builder.push(Action::StayFor(5.)))
+            .and_then(|builder| builder.push(Action::TakePhoto))
+            .and_then(|builder| builder.push(Action::StopRecording))
+            .expect("Sequence should be valid")
+            .build();
+
+        assert_eq!(actions.len(), 4);
+    }
+
+    #[test]
+    fn test_invalid_action_sequence() {
+        let result = ActionSequenceBuilder::new().push(Action::StopRecording);
+
+        assert!(matches!(result, Err(LitchiError::InvalidActionSequence(_))));
+    }
+
+    #[test]
+    fn test_try_to_binary() {
+        let mission = LitchiMission {
+            waypoints: vec![Waypoint {
+                poi_index: Some(i32::MAX as usize + 1),
+                ..waypoint(48., 11.)
+            }],
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            mission.try_to_binary(),
+            Err(LitchiError::PoiIndexOverflow {
+                waypoint: 0,
+                poi_index
+            }) if poi_index == i32::MAX as usize + 1
+        ));
+        let old_version = LitchiMission {
+            waypoints: vec![waypoint(48., 11.)],
+            config: MissionConfig {
+                version: 10,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(matches!(
+            old_version.try_to_binary(),
+            Err(LitchiError::UnsupportedVersion { found: 10 })
+        ));
+        assert!(matches!(
+            encode_count(usize::MAX, LitchiError::TooManyWaypoints),
+            Err(LitchiError::TooManyWaypoints(usize::MAX))
+        ));
+        assert!(matches!(
+            encode_count(i32::MAX as usize, LitchiError::TooManyPois),
+            Ok(i32::MAX)
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let config = MissionConfig {
+            version: 10,
+            ..Default::default()
+        };
+        assert!(matches!(
+            LitchiMission::new(vec![waypoint(48., 11.)], vec![], config),
+            Err(LitchiError::UnsupportedVersion { found: 10 })
+        ));
+
+        let mut mission =
+            LitchiMission::new(vec![waypoint(48., 11.)], vec![], MissionConfig::default()).unwrap();
+        assert!(mission.validate().is_ok());
+        mission.config_mut().version = 12;
+        assert!(matches!(
+            mission.validate(),
+            Err(LitchiError::UnsupportedVersion { found: 12 })
+        ));
+    }
+
+    #[test]
+    fn test_action_encodings() {
+        let actions = [
+            Action::StayFor(2.5),
+            Action::TakePhoto,
+            Action::StartRecording,
+            Action::StopRecording,
+            Action::RotateAircraft(-90),
+            Action::TiltCamera(-45),
+        ];
+
+        let encodings: Vec<_> = actions
+            .iter()
+            .map(|action| {
+                let (action_type, param) = action.idx_and_param().unwrap();
+                assert_eq!(
+                    Action::from_idx_and_param(action_type, param).unwrap(),
+                    *action
+                );
+                format!("{action:?} => {action_type}, {param}")
+            })
+            .collect();
+        insta::assert_snapshot!(encodings.join("\n"));
+
+        assert!(matches!(
+            Action::from_idx_and_param(6, 200),
+            Err(LitchiError::InvalidActionType {
+                action_type: 6,
+                param: 200
+            })
+        ));
+    }
+
+    #[test]
+    fn test_display() {
+        let mission = LitchiMission::new(
+            vec![waypoint(48., 11.), waypoint(48.001, 11.)],
+            vec![],
+            MissionConfig::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            mission.to_string(),
+            "2 waypoints, 0 POIs, 111 m, heading mode Manual, finish action Rth, cruising at 8 m/s"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mission = LitchiMission::new(
+            vec![
+                Waypoint {
+                    actions: vec![Action::StayFor(1.5), Action::TakePhoto],
+                    photo_interval: Some(PhotoInterval::Distance(10.)),
+                    poi_index: Some(0),
+                    ..waypoint(48., 11.)
+                },
+                waypoint(48.001, 11.),
+            ],
+            vec![POI {
+                coordinate: Coordinate(48.0005, 11.001),
+                altitude: 5.,
+                altitude_mode: AltitudeMode::AboveGround,
+            }],
+            MissionConfig::default(),
+        )
+        .unwrap();
+
+        let json = serde_json::to_value(&mission).unwrap();
+        assert_eq!(
+            json["waypoints"][0]["actions"][0],
+            serde_json::json!({ "type": "StayFor", "param": 1.5 })
+        );
+        assert_eq!(json["config"]["heading_mode"], "Manual");
+
+        let parsed: LitchiMission = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(parsed, mission);
+
+        let mut invalid = json.clone();
+        invalid["waypoints"][0]["poi_index"] = serde_json::json!(3);
+        assert!(serde_json::from_value::<LitchiMission>(invalid).is_err());
+
+        let mut inverted = json;
+        inverted["constraints"]["cruising_speed"] =
+            serde_json::json!({ "start": 15., "end": -15. });
+        assert!(serde_json::from_value::<LitchiMission>(inverted).is_err());
+    }
+
+    #[test]
+    fn test_simplify() {
+        let line: Vec<_> = (0..10)
+            .map(|index| waypoint(48. + index as f64 * 0.001, 11.))
+            .collect();
+        let mut mission =
+            LitchiMission::new(line.clone(), vec![], MissionConfig::default()).unwrap();
+
+        assert_eq!(mission.simplify(1.), 8);
+        assert_eq!(mission.waypoints(), &[line[0].clone(), line[9].clone()]);
+
+        // The corner of an L and a waypoint with an action stay
+        let mut waypoints: Vec<_> = (0..10)
+            .map(|index: usize| {
+                waypoint(
+                    48. + index.min(5) as f64 * 0.001,
+                    11. + index.saturating_sub(5) as f64 * 0.0015,
+                )
+            })
+            .collect();
+        waypoints[2].actions = vec![Action::TakePhoto];
+        let mut mission = LitchiMission::new(waypoints, vec![], MissionConfig::default()).unwrap();
+
+        assert_eq!(mission.simplify(1.), 6);
+        let kept: Vec<_> = mission
+            .waypoints()
+            .iter()
+            .map(|waypoint| waypoint.coordinate.clone())
+            .collect();
+        assert_eq!(
+            kept,
+            [
+                Coordinate(48., 11.),
+                Coordinate(48.002, 11.),
+                Coordinate(48.005, 11.),
+                Coordinate(48.005, 11.006)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_distance_to_leg() {
+        let start = Coordinate(48., 11.);
+        let end = Coordinate(48.001, 11.);
+
+        let beside = Coordinate(48.0005, 11.001).distance_to_leg(&start, &end);
+        assert!(
+            (beside - Coordinate(48.0005, 11.).distance_to(&Coordinate(48.0005, 11.001))).abs()
+                < 0.1
+        );
+        let before = Coordinate(47.999, 11.).distance_to_leg(&start, &end);
+        assert!((before - start.distance_to(&Coordinate(47.999, 11.))).abs() < 1e-6);
+        let beyond = Coordinate(48.002, 11.).distance_to_leg(&start, &end);
+        assert!((beyond - end.distance_to(&Coordinate(48.002, 11.))).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_reverse() {
+        let poi = POI {
+            coordinate: Coordinate(48.0005, 11.001),
+            altitude: 5.,
+            altitude_mode: AltitudeMode::AboveGround,
+        };
+        let waypoints = vec![
+            Waypoint {
+                poi_index: Some(0),
+                actions: vec![Action::TakePhoto],
+                ..waypoint(48., 11.)
+            },
+            waypoint(48.001, 11.),
+            waypoint(48.001, 11.001),
+        ];
+        let mission = LitchiMission::new(
+            waypoints.clone(),
+            vec![poi.clone()],
+            MissionConfig::default(),
+        )
+        .unwrap();
+
+        let mut reversed = mission.clone();
+        reversed.reverse(false);
+        assert_eq!(reversed.waypoints()[0], waypoints[2]);
+        assert_eq!(reversed.waypoints()[2], waypoints[0]);
+        let last = &reversed.waypoints()[2];
+        assert_eq!(reversed.pois()[last.poi_index.unwrap()], poi);
+        reversed.validate().unwrap();
+
+        let mut reversed = mission;
+        reversed.reverse(true);
+        let headings: Vec<_> = reversed
+            .waypoints()
+            .iter()
+            .map(|waypoint| waypoint.heading.round())
+            .collect();
+        // West towards the second waypoint, west along the leg, then facing the POI to the north east
+        assert_eq!(headings[..2], [-90., -90.]);
+        assert!((0.0..90.).contains(&headings[2]));
+    }
+
+    #[test]
+    fn test_append() {
+        let poi = |longitude| POI {
+            coordinate: Coordinate(48.0005, longitude),
+            altitude: 5.,
+            altitude_mode: AltitudeMode::AboveGround,
+        };
+        let focusing = |latitude, longitude, poi_index| Waypoint {
+            poi_index: Some(poi_index),
+            ..waypoint(latitude, longitude)
+        };
+        let mut mission = LitchiMission::new(
+            vec![focusing(48., 11., 0), focusing(48.001, 11., 1)],
+            vec![poi(11.001), poi(11.002)],
+            MissionConfig::default(),
+        )
+        .unwrap();
+        let other = LitchiMission::new(
+            vec![focusing(48.002, 11., 0), focusing(48.003, 11., 1)],
+            vec![poi(11.003), poi(11.002)],
+            MissionConfig::default(),
+        )
+        .unwrap();
+
+        mission.append(&other).unwrap();
+
+        assert_eq!(mission.waypoints().len(), 4);
+        assert_eq!(mission.pois().len(), 3);
+        let poi_indices: Vec<_> = mission
+            .waypoints()
+            .iter()
+            .map(|waypoint| waypoint.poi_index)
+            .collect();
+        assert_eq!(poi_indices, [Some(0), Some(1), Some(2), Some(1)]);
+        mission.validate().unwrap();
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        assert_eq!(LitchiMission::default().bounding_box(), None);
+        assert_eq!(LitchiMission::default().centroid(), None);
+
+        let mission = LitchiMission::new(
+            vec![
+                waypoint(48.002, 11.),
+                waypoint(47.998, 11.004),
+                waypoint(48., 10.998),
+                waypoint(48.004, 11.002),
+            ],
+            vec![POI {
+                coordinate: Coordinate(48.001, 11.006),
+                altitude: 5.,
+                altitude_mode: AltitudeMode::AboveGround,
+            }],
+            MissionConfig::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            mission.bounding_box(),
+            Some((Coordinate(47.998, 10.998), Coordinate(48.004, 11.006)))
+        );
+        let centroid = mission.centroid().unwrap();
+        assert!((centroid.0 - 48.001).abs() < 1e-9);
+        assert!((centroid.1 - 11.001).abs() < 1e-9);
     }
 }
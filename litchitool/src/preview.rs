@@ -0,0 +1,131 @@
+use tiny_skia::{Color, FillRule, Paint, PathBuilder, Pixmap, Stroke, Transform};
+
+use crate::{
+    error::LitchiError,
+    mission::{Coordinate, LitchiMission},
+};
+
+/// Space in pixels kept free around the mission
+const MARGIN: f32 = 8.;
+const WAYPOINT_RADIUS: f32 = 3.;
+const POI_RADIUS: f32 = 4.;
+
+/// Renders the waypoint path and POIs on a white background as a PNG thumbnail. The mission is
+/// scaled to fit the image, keeping its aspect ratio, north pointing up.
+pub fn render_png(
+    mission: &LitchiMission,
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, LitchiError> {
+    let mut pixmap = Pixmap::new(width, height)
+        .ok_or_else(|| LitchiError::PreviewError(format!("Invalid image size {width}x{height}")))?;
+    pixmap.fill(Color::WHITE);
+
+    let Some(origin) = mission.centroid() else {
+        // Nothing to draw
+        return encode(&pixmap);
+    };
+
+    // Positions in meters east and north of the centroid
+    let to_meters = |coordinate: &Coordinate| {
+        let (north, east) = coordinate.offset_from(&origin);
+        (east as f32, north as f32)
+    };
+    let waypoints: Vec<_> = mission
+        .waypoints()
+        .iter()
+        .map(|waypoint| to_meters(&waypoint.coordinate))
+        .collect();
+    let pois: Vec<_> = mission
+        .pois()
+        .iter()
+        .map(|poi| to_meters(&poi.coordinate))
+        .collect();
+
+    let (min_x, max_x, min_y, max_y) = waypoints.iter().chain(&pois).fold(
+        (
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+        ),
+        |(min_x, max_x, min_y, max_y), (x, y)| {
+            (min_x.min(*x), max_x.max(*x), min_y.min(*y), max_y.max(*y))
+        },
+    );
+    let span = (max_x - min_x).max(max_y - min_y).max(1.);
+    let scale = ((width.min(height) as f32) - 2. * MARGIN).max(1.) / span;
+    let center = ((min_x + max_x) / 2., (min_y + max_y) / 2.);
+    let to_pixels = |(x, y): (f32, f32)| {
+        (
+            width as f32 / 2. + (x - center.0) * scale,
+            height as f32 / 2. - (y - center.1) * scale,
+        )
+    };
+
+    let mut paint = Paint {
+        anti_alias: true,
+        ..Default::default()
+    };
+
+    let mut path = PathBuilder::new();
+    for (index, point) in waypoints.iter().enumerate() {
+        let (x, y) = to_pixels(*point);
+        if index == 0 {
+            path.move_to(x, y);
+        } else {
+            path.line_to(x, y);
+        }
+    }
+    // A single waypoint has no path to draw
+    if let Some(path) = path.finish() {
+        paint.set_color_rgba8(30, 100, 200, 255);
+        let stroke = Stroke {
+            width: 2.,
+            ..Default::default()
+        };
+        pixmap.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
+    }
+
+    let mut draw_dots = |points: &[(f32, f32)], radius: f32, paint: &Paint| {
+        for point in points {
+            let (x, y) = to_pixels(*point);
+            if let Some(dot) = PathBuilder::from_circle(x, y, radius) {
+                pixmap.fill_path(&dot, paint, FillRule::Winding, Transform::identity(), None);
+            }
+        }
+    };
+    paint.set_color_rgba8(20, 40, 120, 255);
+    draw_dots(&waypoints, WAYPOINT_RADIUS, &paint);
+    paint.set_color_rgba8(220, 60, 40, 255);
+    draw_dots(&pois, POI_RADIUS, &paint);
+
+    encode(&pixmap)
+}
+
+fn encode(pixmap: &Pixmap) -> Result<Vec<u8>, LitchiError> {
+    pixmap
+        .encode_png()
+        .map_err(|error| LitchiError::PreviewError(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv_format;
+
+    const TEST_MISSION_CSV: &[u8] = include_bytes!("../test/litchi_mission.csv");
+
+    #[test]
+    fn test_render_png() {
+        let mission = csv_format::read_from_csv(csv::Reader::from_reader(TEST_MISSION_CSV))
+            .expect("Could not parse test mission from csv");
+
+        let png = render_png(&mission, 200, 120).expect("Could not render preview");
+
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        // Width and height of the IHDR chunk, which always comes first
+        assert_eq!(png[16..20], 200u32.to_be_bytes());
+        assert_eq!(png[20..24], 120u32.to_be_bytes());
+    }
+}
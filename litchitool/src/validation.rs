@@ -0,0 +1,222 @@
+use std::fmt;
+
+use crate::mission::{GimbalPitchMode, LitchiMission};
+
+/// Altitude range in meters across the waypoints focusing a POI above which its framing is
+/// flagged as inconsistent
+pub const POI_FRAMING_ALTITUDE_SPREAD: f32 = 50.;
+
+/// Quality problem found in a mission that Litchi would still accept
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationIssue {
+    /// The waypoints focusing the POI with their gimbal fly at very different altitudes, so the
+    /// framing changes a lot. An interpolated gimbal pitch may work better.
+    InconsistentPoiFraming { poi: usize },
+    /// The speed is outside of the [`crate::mission::MissionConstraints`] and gets clamped
+    /// when writing the binary
+    SpeedClamped { field: &'static str, speed: f32 },
+    /// The waypoint has a negative curve size
+    NegativeCurveSize { waypoint: usize },
+    /// The waypoint's curve size is above [`crate::mission::MissionConstraints::max_curve_size`]
+    /// or larger than the whole mission, it was probably entered in another unit
+    OversizedCurveSize { waypoint: usize, curve_size: f32 },
+    /// Flying the leg from the waypoint to the next one at its speed takes longer than its
+    /// `max_reach_time`, so Litchi aborts the mission before arriving
+    LegExceedsMaxReachTime { waypoint: usize },
+}
+
+impl LitchiMission {
+    /// Checks the mission for problems that don't make it invalid, but are likely mistakes
+    pub fn validation_notes(&self) -> Vec<ValidationIssue> {
+        let mut issues = vec![];
+
+        let config = self.config();
+        let speeds = [
+            (
+                "cruising speed",
+                config.cruising_speed,
+                &self.constraints().cruising_speed,
+            ),
+            ("rc speed", config.rc_speed, &self.constraints().rc_speed),
+        ];
+        for (field, speed, range) in speeds {
+            if !range.contains(&speed) {
+                issues.push(ValidationIssue::SpeedClamped { field, speed });
+            }
+        }
+
+        let extent = self
+            .bounding_circle()
+            .map_or(0., |(_, radius)| 2. * radius as f32);
+        for (index, waypoint) in self.waypoints().iter().enumerate() {
+            let curve_size = waypoint.curve_size;
+            if curve_size < 0. {
+                issues.push(ValidationIssue::NegativeCurveSize { waypoint: index });
+            } else if curve_size > self.constraints().max_curve_size
+                || (extent > 0. && curve_size > extent)
+            {
+                issues.push(ValidationIssue::OversizedCurveSize {
+                    waypoint: index,
+                    curve_size,
+                });
+            }
+        }
+
+        for (index, leg) in self.waypoints().windows(2).enumerate() {
+            let max_reach_time = leg[0].max_reach_time;
+            let speed = self.leg_speed(&leg[0]).abs();
+            if max_reach_time > 0 && speed > 0. {
+                let duration = leg[0].coordinate.distance_to(&leg[1].coordinate) / speed as f64;
+                if duration > max_reach_time as f64 {
+                    issues.push(ValidationIssue::LegExceedsMaxReachTime { waypoint: index });
+                }
+            }
+        }
+
+        for poi in 0..self.pois().len() {
+            let altitudes = self
+                .waypoints()
+                .iter()
+                .filter(|waypoint| {
+                    waypoint.poi_index == Some(poi)
+                        && matches!(waypoint.gimbal_mode, GimbalPitchMode::FocusPOI)
+                })
+                .map(|waypoint| waypoint.altitude);
+            let (min, max) = altitudes.fold(
+                (f32::INFINITY, f32::NEG_INFINITY),
+                |(min, max), altitude| (min.min(altitude), max.max(altitude)),
+            );
+
+            if max - min > POI_FRAMING_ALTITUDE_SPREAD {
+                issues.push(ValidationIssue::InconsistentPoiFraming { poi });
+            }
+        }
+
+        issues
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InconsistentPoiFraming { poi } => write!(
+                f,
+                "POI {poi} is focused from altitudes more than {POI_FRAMING_ALTITUDE_SPREAD} m apart, consider interpolating the gimbal pitch"
+            ),
+            Self::SpeedClamped { field, speed } => write!(
+                f,
+                "The {field} of {speed} m/s is not supported and will be clamped"
+            ),
+            Self::NegativeCurveSize { waypoint } => {
+                write!(f, "Waypoint {waypoint} has a negative curve size")
+            }
+            Self::OversizedCurveSize {
+                waypoint,
+                curve_size,
+            } => write!(
+                f,
+                "Waypoint {waypoint} has a curve size of {curve_size} m, which is larger than plausible for the mission"
+            ),
+            Self::LegExceedsMaxReachTime { waypoint } => write!(
+                f,
+                "The leg from waypoint {waypoint} takes longer than its max reach time at its speed"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mission::{AltitudeMode, Coordinate, MissionConfig, Waypoint, POI};
+
+    #[test]
+    fn test_inconsistent_poi_framing() {
+        let focus = |latitude: f64, altitude: f32| Waypoint {
+            coordinate: Coordinate(latitude, 11.0),
+            altitude,
+            gimbal_mode: GimbalPitchMode::FocusPOI,
+            poi_index: Some(0),
+            ..Default::default()
+        };
+        let poi = POI {
+            coordinate: Coordinate(48.0, 11.0),
+            altitude: 0.,
+            altitude_mode: AltitudeMode::AboveGround,
+        };
+
+        let low_and_high = LitchiMission::new(
+            vec![focus(48.001, 10.), focus(48.002, 120.)],
+            vec![poi.clone()],
+            MissionConfig::default(),
+        )
+        .unwrap();
+        let level = LitchiMission::new(
+            vec![focus(48.001, 30.), focus(48.002, 40.)],
+            vec![poi],
+            MissionConfig::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            low_and_high.validation_notes(),
+            [ValidationIssue::InconsistentPoiFraming { poi: 0 }]
+        );
+        assert!(level.validation_notes().is_empty());
+    }
+
+    #[test]
+    fn test_curve_size() {
+        let curved = |latitude: f64, curve_size: f32| Waypoint {
+            coordinate: Coordinate(latitude, 11.0),
+            curve_size,
+            ..Default::default()
+        };
+
+        // The waypoints span about 220 m
+        let mission = LitchiMission::new(
+            vec![curved(48.0, 5.), curved(48.001, -2.), curved(48.002, 300.)],
+            vec![],
+            MissionConfig::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            mission.validation_notes(),
+            [
+                ValidationIssue::NegativeCurveSize { waypoint: 1 },
+                ValidationIssue::OversizedCurveSize {
+                    waypoint: 2,
+                    curve_size: 300.
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_leg_exceeds_max_reach_time() {
+        let start = Coordinate(48.0, 11.0);
+        let leg = |max_reach_time: i16| {
+            let slow = Waypoint {
+                coordinate: start.clone(),
+                speed: 2.,
+                stay_time: 60,
+                max_reach_time,
+                ..Default::default()
+            };
+            let far = Waypoint {
+                coordinate: start.offset(500., 0.),
+                ..Default::default()
+            };
+            LitchiMission::new(vec![slow, far], vec![], MissionConfig::default()).unwrap()
+        };
+
+        // 500 m at 2 m/s take 250 s
+        assert_eq!(
+            leg(120).validation_notes(),
+            [ValidationIssue::LegExceedsMaxReachTime { waypoint: 0 }]
+        );
+        assert!(leg(300).validation_notes().is_empty());
+        assert!(leg(0).validation_notes().is_empty());
+    }
+}